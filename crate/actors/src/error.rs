@@ -1,13 +1,30 @@
 use thiserror::Error;
 
 use shared::message::emit::EmitSource;
+use shared::types::{Device, ModelType};
 
 #[derive(Error, Debug)]
 pub enum ActorError {
     #[error("Supervisor Model Process; task_id not found; for {owner}")]
     SupervisorModelProcessTaskIdNotFound { owner: String },
+    #[error("Supervisor Model Process; no {model_type:?} worker running; for {owner}")]
+    SupervisorModelProcessModelTypeNotFound {
+        owner: String,
+        model_type: ModelType,
+    },
     #[error("Supervisor Model Process; echo instruction failed; for {owner}")]
     SupervisorModelProcessEchoFailed { owner: String },
+    #[error("Supervisor Model Process; emit message failed; for {owner}")]
+    SupervisorModelProcessEmitMessageFailed { owner: String },
+    #[error("Supervisor Model Process; worker queue is full; for {owner}")]
+    SupervisorModelProcessQueueFull { owner: String },
+    #[error("Supervisor Model Process; forbidden for this role; for {owner}")]
+    SupervisorModelProcessForbidden { owner: String },
+    #[error("Supervisor Model Process; {model_type:?} does not match the worker; for {owner}")]
+    SupervisorModelProcessModelTypeMismatch {
+        owner: String,
+        model_type: ModelType,
+    },
 
     #[error("Supervisor Run Process; command not implemented")]
     SupervisoRunCommandNotImplemented,
@@ -16,16 +33,78 @@ pub enum ActorError {
     SupervisorModelEmitKillEmitMessageFailed { owner: String },
     #[error("Supervisor Model Kill; task_id not found; for {owner}")]
     SupervisorModelKillEmitTaskIdNotFound { owner: String },
+    #[error("Supervisor Model Kill; forbidden for this role; for {owner}")]
+    SupervisorModelKillForbidden { owner: String },
+    #[error("Supervisor Model Kill; {model_type:?} does not match the worker; for {owner}")]
+    SupervisorModelKillModelTypeMismatch {
+        owner: String,
+        model_type: ModelType,
+    },
+
+    #[error("Supervisor Model Pause; task_id not found; for {owner}")]
+    SupervisorModelPauseTaskIdNotFound { owner: String },
+    #[error("Supervisor Model Pause; forbidden for this role; for {owner}")]
+    SupervisorModelPauseForbidden { owner: String },
+    #[error("Supervisor Model Pause; {model_type:?} does not match the worker; for {owner}")]
+    SupervisorModelPauseModelTypeMismatch {
+        owner: String,
+        model_type: ModelType,
+    },
+    #[error("Supervisor Model Pause; forward instruction failed; for {owner}")]
+    SupervisorModelPauseForwardFailed { owner: String },
+    #[error("Supervisor Model Pause; emit message failed; for {owner}")]
+    SupervisorModelPauseEmitMessageFailed { owner: String },
+
+    #[error("Supervisor Model Resume; task_id not found; for {owner}")]
+    SupervisorModelResumeTaskIdNotFound { owner: String },
+    #[error("Supervisor Model Resume; forbidden for this role; for {owner}")]
+    SupervisorModelResumeForbidden { owner: String },
+    #[error("Supervisor Model Resume; {model_type:?} does not match the worker; for {owner}")]
+    SupervisorModelResumeModelTypeMismatch {
+        owner: String,
+        model_type: ModelType,
+    },
+    #[error("Supervisor Model Resume; forward instruction failed; for {owner}")]
+    SupervisorModelResumeForwardFailed { owner: String },
+    #[error("Supervisor Model Resume; emit message failed; for {owner}")]
+    SupervisorModelResumeEmitMessageFailed { owner: String },
+
+    #[error("Supervisor Model Cancel; job_id not found; for {owner}")]
+    SupervisorModelCancelJobIdNotFound { owner: String },
+    #[error("Supervisor Model Cancel; task_id not found; for {owner}")]
+    SupervisorModelCancelTaskIdNotFound { owner: String },
+    #[error("Supervisor Model Cancel; forbidden for this role; for {owner}")]
+    SupervisorModelCancelForbidden { owner: String },
+    #[error("Supervisor Model Cancel; {model_type:?} does not match the job; for {owner}")]
+    SupervisorModelCancelModelTypeMismatch {
+        owner: String,
+        model_type: ModelType,
+    },
 
     #[error("Supervisor Task Stats; task_id not found; for {owner}")]
     SupervisorTaskStatsTaskIdNotFound { owner: String },
     #[error("Supervisor Task Stats; emit message failed; for {owner}")]
     SupervisorEmitTaskStatsEmitMessageFailed { owner: String },
 
+    #[error("Supervisor Job Tracker; cannot spawn tracker; for {owner}")]
+    SupervisorJobTrackerSpawnFailed { owner: String },
+    #[error("Supervisor Queue; cannot spawn position signal; for {owner}")]
+    SupervisorQueueSignalSpawnFailed { owner: String },
+    #[error("Supervisor Watchdog; cannot spawn watchdog; for {owner}")]
+    SupervisorWatchdogSpawnFailed { owner: String },
+    #[error("Supervisor Evictor; cannot spawn evictor; for {owner}")]
+    SupervisorEvictorSpawnFailed { owner: String },
+    #[error("Supervisor Metrics Tracker; cannot spawn tracker; for {owner}")]
+    SupervisorMetricsTrackerSpawnFailed { owner: String },
+
     #[error("Supervisor Model Spawn; insert task_id failed; for {owner}")]
     SupervisorModelSpawnInsertTaskIdFailed { owner: String },
     #[error("Supervisor Model Spawn; cannot spawn model; for {owner}")]
     SupervisorModelSpawnModelSpawnFailed { owner: String },
+    #[error("Supervisor Model Spawn; forbidden for this role; for {owner}")]
+    SupervisorModelSpawnForbidden { owner: String },
+    #[error("Supervisor Model Spawn; device {device} not found; for {owner}")]
+    SupervisorModelSpawnDeviceNotFound { owner: String, device: Device },
 
     #[error("Worker Run; parse json input failed; for {owner} and {source}")]
     WorkerRunParseJsonInputFailed { owner: String, source: EmitSource },
@@ -0,0 +1,27 @@
+use shared::types::ModelType;
+
+use super::job::JobRegister;
+use super::ModelRegister;
+
+/// Number of jobs queued on or running in the given worker.
+pub(crate) fn depth(jobs: &JobRegister, task_id: &str) -> usize {
+    jobs.iter().filter(|job| job.value().is_on(task_id)).count()
+}
+
+/// Picks the worker of `model_type` with the fewest jobs in flight among the
+/// ones whose owner is `allowed`. Ties go to the lowest task id so the choice
+/// stays stable between two calls.
+pub(crate) fn least_loaded(
+    register_map: &ModelRegister,
+    jobs: &JobRegister,
+    model_type: ModelType,
+    allowed: impl Fn(&str) -> bool,
+) -> Option<String> {
+    register_map
+        .iter()
+        .filter(|register| register.value().model_type == model_type)
+        .filter(|register| allowed(&register.value().owner))
+        .map(|register| (depth(jobs, register.key()), register.key().clone()))
+        .min()
+        .map(|(_, task_id)| task_id)
+}
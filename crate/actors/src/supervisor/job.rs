@@ -0,0 +1,116 @@
+use dashmap::DashMap;
+use std::{sync::Arc, time::Instant};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use shared::{
+    message::{emit::EmitSource, Message},
+    tools::root,
+    types::JobStatus,
+};
+
+use super::ModelRegister;
+use crate::error::ActorError;
+
+#[derive(Debug)]
+pub(crate) struct Job {
+    pub source: EmitSource,
+    pub status: JobStatus,
+    pub queued_at: Instant,
+}
+
+impl Job {
+    pub fn queued(source: EmitSource) -> Self {
+        Self {
+            source,
+            status: JobStatus::Queued,
+            queued_at: Instant::now(),
+        }
+    }
+
+    pub fn is_on(&self, task_id: &str) -> bool {
+        self.source.task_id().as_deref() == Some(task_id)
+    }
+
+    pub fn is_waiting_on(&self, task_id: &str) -> bool {
+        self.status == JobStatus::Queued && self.is_on(task_id)
+    }
+}
+
+/// Live jobs by job id; a job leaves the register once it reaches a terminal state.
+pub(crate) type JobRegister = Arc<DashMap<String, Job>>;
+
+pub(crate) fn update(jobs: &JobRegister, message: &Message) {
+    let (Some(job_id), Some(status)) = (message.job_id(), message.job_status()) else {
+        return;
+    };
+
+    let is_terminal = match jobs.get_mut(&job_id) {
+        Some(mut job) if job.status.can_move_to(status) => {
+            job.status = status;
+            status.is_terminal()
+        }
+        _ => false,
+    };
+
+    if is_terminal {
+        jobs.remove(&job_id);
+    }
+}
+
+/// Removes and returns every job routed to the given worker.
+pub(crate) fn drain(jobs: &JobRegister, task_id: &str) -> Vec<Job> {
+    let job_ids: Vec<String> = jobs
+        .iter()
+        .filter(|job| job.value().is_on(task_id))
+        .map(|job| job.key().clone())
+        .collect();
+
+    job_ids
+        .iter()
+        .filter_map(|job_id| jobs.remove(job_id).map(|(_, job)| job))
+        .collect()
+}
+
+/// Drops the jobs whose end was missed: their worker is gone, or released
+/// them already.
+pub(crate) fn reconcile(jobs: &JobRegister, registers: &ModelRegister) {
+    // Collected first, so that no job is locked while a worker is looked up.
+    let routed: Vec<(String, Option<String>)> = jobs
+        .iter()
+        .map(|job| (job.key().clone(), job.value().source.task_id()))
+        .collect();
+
+    for (job_id, task_id) in routed {
+        let pending = task_id
+            .and_then(|task_id| registers.get(&task_id))
+            .is_some_and(|register| register.control.is_pending(&job_id));
+        if !pending {
+            jobs.remove(&job_id);
+        }
+    }
+}
+
+/// Follows the jobs on the bus; after a lag, the register is reconciled with
+/// the jobs the workers still hold.
+pub(crate) async fn tracker(
+    tx: &broadcast::Sender<Message>,
+    jobs: JobRegister,
+    registers: ModelRegister,
+) -> Result<(), ActorError> {
+    let mut rx = tx.subscribe();
+    tokio::task::Builder::new()
+        .name("jobs")
+        .spawn(async move {
+            loop {
+                match rx.recv().await {
+                    Ok(message) => update(&jobs, &message),
+                    Err(RecvError::Lagged(_)) => reconcile(&jobs, &registers),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        })
+        .and(Ok(()))
+        .or(Err(ActorError::SupervisorJobTrackerSpawnFailed {
+            owner: root(),
+        }))
+}
@@ -0,0 +1,151 @@
+use dashmap::DashMap;
+use std::{collections::VecDeque, sync::Arc, time::Instant};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use shared::{
+    message::Message,
+    tools::root,
+    types::{health::Latency, HealthReport, WorkerHealth, WorkerState},
+};
+
+use super::job::JobRegister;
+use super::{queue, ModelRegister};
+use crate::error::ActorError;
+
+/// Number of recent jobs the latency percentiles are computed over.
+const LATENCY_WINDOW: usize = 100;
+
+#[derive(Debug, Default)]
+pub(crate) struct Metrics {
+    pub loaded: bool,
+    pub paused: bool,
+    pub running_since: Option<Instant>,
+    pub processed: u64,
+    pub failures: u64,
+    pub latencies: VecDeque<u64>,
+    pub device: Option<String>,
+    pub memory: Option<u64>,
+}
+
+impl Metrics {
+    pub fn state(&self) -> WorkerState {
+        if self.paused {
+            WorkerState::Paused
+        } else if self.running_since.is_some() {
+            WorkerState::Busy
+        } else if self.loaded {
+            WorkerState::Idle
+        } else {
+            WorkerState::Loading
+        }
+    }
+
+    pub fn latency(&self) -> Latency {
+        let samples: Vec<u64> = self.latencies.iter().copied().collect();
+        Latency::from_samples(&samples)
+    }
+
+    fn done(&mut self) {
+        if let Some(since) = self.running_since.take() {
+            if self.latencies.len() == LATENCY_WINDOW {
+                self.latencies.pop_front();
+            }
+            self.latencies.push_back(since.elapsed().as_millis() as u64);
+        }
+        self.processed += 1;
+    }
+
+    fn failed(&mut self) {
+        self.running_since = None;
+        self.failures += 1;
+    }
+}
+
+/// Worker metrics by task id, fed by the messages the workers broadcast.
+pub(crate) type MetricsRegister = Arc<DashMap<String, Metrics>>;
+
+pub(crate) fn update(metrics: &MetricsRegister, message: &Message) {
+    let Some(task_id) = message.task_id() else {
+        return;
+    };
+
+    match message {
+        Message::ModelKilled(_) | Message::ModelCrashed(_) | Message::ModelEvicted(_) => {
+            metrics.remove(&task_id);
+            return;
+        }
+        Message::Health(_) => return,
+        _ => (),
+    }
+
+    let mut worker = metrics.entry(task_id).or_default();
+    match message {
+        Message::ModelLoaded(data) => {
+            worker.loaded = true;
+            worker.device = data.device.clone();
+            worker.memory = data.memory;
+        }
+        Message::ModelPaused(_) => worker.paused = true,
+        Message::ModelResumed(_) => worker.paused = false,
+        Message::JobRunning(_) => worker.running_since = Some(Instant::now()),
+        Message::JobDone(_) => worker.done(),
+        Message::JobFailed(_) => worker.failed(),
+        Message::JobCancelled(_) => worker.running_since = None,
+        _ => (),
+    }
+}
+
+pub(crate) async fn tracker(
+    tx: &broadcast::Sender<Message>,
+    metrics: MetricsRegister,
+) -> Result<(), ActorError> {
+    let mut rx = tx.subscribe();
+    tokio::task::Builder::new()
+        .name("metrics")
+        .spawn(async move {
+            loop {
+                match rx.recv().await {
+                    Ok(message) => update(&metrics, &message),
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        })
+        .and(Ok(()))
+        .or(Err(ActorError::SupervisorMetricsTrackerSpawnFailed {
+            owner: root(),
+        }))
+}
+
+pub(crate) fn report(
+    registers: &ModelRegister,
+    metrics: &MetricsRegister,
+    jobs: &JobRegister,
+) -> HealthReport {
+    let workers = registers
+        .iter()
+        .map(|register| {
+            let task_id = register.key().clone();
+            let worker = metrics.get(&task_id);
+            let worker = worker.as_deref();
+            WorkerHealth {
+                owner: register.value().owner.clone(),
+                model_type: register.value().model_type,
+                state: worker.map_or(WorkerState::Loading, Metrics::state),
+                processed: worker.map_or(0, |worker| worker.processed),
+                failures: worker.map_or(0, |worker| worker.failures),
+                latency: worker.map(Metrics::latency).unwrap_or_default(),
+                queue_depth: queue::waiting(jobs, &task_id).len(),
+                device: worker.and_then(|worker| worker.device.clone()),
+                memory: worker.and_then(|worker| worker.memory),
+                task_id,
+            }
+        })
+        .collect();
+
+    HealthReport {
+        workers,
+        memory: shared::tools::resident_memory(),
+        jobs: jobs.iter().map(|job| job.key().clone()).collect(),
+    }
+}
@@ -1,22 +1,43 @@
+mod dispatch;
+mod job;
+mod metrics;
+mod placement;
+mod queue;
+mod reload;
+mod residency;
+mod settings;
+mod shutdown;
+mod watchdog;
+
+pub use settings::Settings;
+pub use watchdog::RestartPolicies;
+
 use crate::error::ActorError;
 use shared::constants;
 
-use crate::worker::{diffusion, llama, sentiment, summarize, translation};
-use dashmap::{mapref::multiple::RefMulti, DashMap};
-use shared::types::MessageType;
-use shared::types::ModelType;
+use crate::worker;
+use dashmap::DashMap;
+use job::{Job, JobRegister};
+use metrics::MetricsRegister;
+use models::{
+    diffusion::Diffusion, llama::Llama, sentiment::Sentiment, summarize::Summarize,
+    translation::Translation,
+};
+use shared::types::{CommandType, MessageType};
+use shared::types::{Device, ModelType};
 
 use shared::{
     command::{instruction, instruction::Instruction, Command},
+    control::Control,
     message::{
-        emit::{Emit, EmitSource},
+        emit::{self, Emit, EmitSource},
         Message,
     },
     tools::root,
 };
-use std::{collections::hash_map::RandomState, sync::Arc};
+use std::{sync::Arc, time::Instant};
 use tokio::{
-    sync::{broadcast, mpsc},
+    sync::{broadcast, mpsc, watch},
     task::JoinHandle,
 };
 
@@ -26,84 +47,131 @@ type TaskHandle = JoinHandle<Result<(), String>>;
 struct Register {
     handle: TaskHandle,
     tx: mpsc::Sender<Box<dyn Instruction>>,
+    control: Control,
     owner: String,
     model_type: ModelType,
-}
-
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
-struct RegisterJson {
-    task_id: String,
-    owner: String,
-    model_type: ModelType,
-}
-
-impl From<RefMulti<'_, String, Register, RandomState>> for RegisterJson {
-    fn from(value: RefMulti<String, Register, RandomState>) -> Self {
-        RegisterJson {
-            task_id: value.key().clone(),
-            owner: value.value().owner.clone(),
-            model_type: value.value().model_type,
-        }
-    }
+    device: Device,
+    restarts: u32,
+    last_used: Instant,
 }
 
 type ModelRegister = Arc<DashMap<String, Register>>;
 
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
-struct MapJson {
-    value: Vec<RegisterJson>,
-}
-
-impl From<ModelRegister> for MapJson {
-    fn from(value: ModelRegister) -> Self {
-        Self {
-            value: value.iter().map(RegisterJson::from).collect(),
-        }
-    }
-}
-
 async fn model_process(
     register_map: &ModelRegister,
-    instruction: instruction::Process,
+    jobs: &JobRegister,
+    tx: &broadcast::Sender<Message>,
+    mut instruction: instruction::Process,
+    settings: &Settings,
 ) -> Result<(), ActorError> {
     let owner = instruction.owner();
     let model_type = instruction.model_type();
-    let task_id = instruction.task_id();
+    let role = instruction.role;
+    let may = |worker_owner: &str| role.may(CommandType::Process, model_type, &owner, worker_owner);
 
-    let tx = match task_id {
-        Some(task_id) => {
-            let pair = register_map.get(&task_id).ok_or(
-                ActorError::SupervisorModelProcessTaskIdNotFound {
+    let task_id = match instruction.task_id() {
+        Some(task_id) => task_id,
+        None => match dispatch::least_loaded(register_map, jobs, model_type, may) {
+            Some(task_id) => task_id,
+            None if settings.residency.lazy => {
+                let spawn = instruction::Spawn::new(constants::role::ROOT, model_type);
+                model_spawn(&mut register_map.clone(), tx.clone(), spawn, 0, settings).await?
+            }
+            None => {
+                return Err(ActorError::SupervisorModelProcessModelTypeNotFound {
                     owner: instruction.owner(),
-                },
-            )?;
-            let Register { tx, .. } = pair.value();
-            tx.clone()
+                    model_type,
+                })
+            }
+        },
+    };
+
+    let worker_tx = {
+        let mut register = register_map.get_mut(&task_id).ok_or(
+            ActorError::SupervisorModelProcessTaskIdNotFound {
+                owner: instruction.owner(),
+            },
+        )?;
+        if !may(&register.owner) {
+            return Err(ActorError::SupervisorModelProcessForbidden { owner });
         }
-        None => {
-            let pair = register_map
-                .iter()
-                .find(|value| value.value().model_type == model_type)
-                .unwrap();
-            let Register { tx, .. } = pair.value();
-            tx.clone()
+        if register.model_type != model_type {
+            return Err(ActorError::SupervisorModelProcessModelTypeMismatch { owner, model_type });
         }
+        register.last_used = Instant::now();
+        register.tx.clone()
     };
 
-    tx.send(Box::new(instruction))
-        .await
-        .map_err(|_| ActorError::SupervisorModelProcessEchoFailed { owner })
+    instruction.task_id = Some(task_id.clone());
+    let job_id = instruction.job_id.clone();
+    let boxed_instruction: Box<dyn Instruction> = Box::new(instruction.clone());
+    let source = EmitSource::from(boxed_instruction);
+
+    let capacity = settings.queue_len;
+    if queue::is_full(jobs, &task_id, capacity) {
+        if let Err(err) = emit::queue_full(tx, source, capacity) {
+            println!("{err:#?}");
+        }
+        return Err(ActorError::SupervisorModelProcessQueueFull { owner });
+    }
+
+    if let Some(register) = register_map.get(&task_id) {
+        register.control.queue(&job_id);
+    }
+    jobs.insert(job_id.clone(), Job::queued(source.clone()));
+    if let Err(err) = worker_tx.try_send(Box::new(instruction)) {
+        jobs.remove(&job_id);
+        if let Some(register) = register_map.get(&task_id) {
+            register.control.unqueue(&job_id);
+        }
+        return Err(match err {
+            mpsc::error::TrySendError::Full(_) => {
+                ActorError::SupervisorModelProcessQueueFull { owner }
+            }
+            mpsc::error::TrySendError::Closed(_) => {
+                ActorError::SupervisorModelProcessEchoFailed { owner }
+            }
+        });
+    }
+
+    MessageType::JobQueued
+        .emit(tx, source, None)
+        .map_err(|_| ActorError::SupervisorModelProcessEmitMessageFailed { owner })
+        .map(|_| ())
 }
 
 async fn model_kill(
     register_map: &mut ModelRegister,
+    jobs: &JobRegister,
     tx: &broadcast::Sender<Message>,
     instruction: instruction::Kill,
 ) -> Result<(), ActorError> {
     let owner = instruction.owner();
-    if let Some(register) = register_map.get_mut(&instruction.task_id().unwrap()) {
+    let model_type = instruction.model_type();
+    let target = register_map
+        .get(&instruction.task_id)
+        .map(|register| (register.owner.clone(), register.model_type));
+    if let Some((worker_owner, worker_model_type)) = target {
+        if !instruction
+            .role
+            .may(CommandType::Kill, worker_model_type, &owner, &worker_owner)
+        {
+            return Err(ActorError::SupervisorModelKillForbidden { owner });
+        }
+        if worker_model_type != model_type {
+            return Err(ActorError::SupervisorModelKillModelTypeMismatch { owner, model_type });
+        }
+    }
+
+    if let Some((_, register)) = register_map.remove(&instruction.task_id) {
         register.handle.abort();
 
+        for job in job::drain(jobs, &instruction.task_id) {
+            if let Err(err) = MessageType::JobFailed.emit(tx, job.source, Some("Model killed")) {
+                println!("{err:#?}");
+            }
+        }
+
         let boxed_instruction: Box<dyn Instruction> = Box::new(instruction);
         MessageType::ModelKilled
             .emit(tx, boxed_instruction.into(), None)
@@ -114,21 +182,149 @@ async fn model_kill(
     }
 }
 
+async fn model_pause(
+    register_map: &ModelRegister,
+    tx: &broadcast::Sender<Message>,
+    instruction: instruction::Pause,
+) -> Result<(), ActorError> {
+    let owner = instruction.owner();
+    let worker_tx = {
+        let register = register_map.get(&instruction.task_id).ok_or(
+            ActorError::SupervisorModelPauseTaskIdNotFound {
+                owner: owner.clone(),
+            },
+        )?;
+        if !instruction.role.may(
+            CommandType::Pause,
+            register.model_type,
+            &owner,
+            &register.owner,
+        ) {
+            return Err(ActorError::SupervisorModelPauseForbidden { owner });
+        }
+        if register.model_type != instruction.model_type() {
+            return Err(ActorError::SupervisorModelPauseModelTypeMismatch {
+                owner,
+                model_type: instruction.model_type(),
+            });
+        }
+        register.control.pause();
+        register.tx.clone()
+    };
+
+    worker_tx
+        .send(Box::new(instruction.clone()))
+        .await
+        .map_err(|_| ActorError::SupervisorModelPauseForwardFailed {
+            owner: owner.clone(),
+        })?;
+
+    let boxed_instruction: Box<dyn Instruction> = Box::new(instruction);
+    MessageType::ModelPaused
+        .emit(tx, boxed_instruction.into(), None)
+        .map_err(|_| ActorError::SupervisorModelPauseEmitMessageFailed { owner })
+        .map(|_| ())
+}
+
+async fn model_resume(
+    register_map: &ModelRegister,
+    tx: &broadcast::Sender<Message>,
+    instruction: instruction::Resume,
+) -> Result<(), ActorError> {
+    let owner = instruction.owner();
+    let worker_tx = {
+        let register = register_map.get(&instruction.task_id).ok_or(
+            ActorError::SupervisorModelResumeTaskIdNotFound {
+                owner: owner.clone(),
+            },
+        )?;
+        if !instruction.role.may(
+            CommandType::Resume,
+            register.model_type,
+            &owner,
+            &register.owner,
+        ) {
+            return Err(ActorError::SupervisorModelResumeForbidden { owner });
+        }
+        if register.model_type != instruction.model_type() {
+            return Err(ActorError::SupervisorModelResumeModelTypeMismatch {
+                owner,
+                model_type: instruction.model_type(),
+            });
+        }
+        register.control.resume();
+        register.tx.clone()
+    };
+
+    worker_tx
+        .send(Box::new(instruction.clone()))
+        .await
+        .map_err(|_| ActorError::SupervisorModelResumeForwardFailed {
+            owner: owner.clone(),
+        })?;
+
+    let boxed_instruction: Box<dyn Instruction> = Box::new(instruction);
+    MessageType::ModelResumed
+        .emit(tx, boxed_instruction.into(), None)
+        .map_err(|_| ActorError::SupervisorModelResumeEmitMessageFailed { owner })
+        .map(|_| ())
+}
+
+/// Flags the job as cancelled on the worker it was routed to, when the role
+/// of the sender allows it. The worker
+/// drops it if still queued, or stops the model at its next checkpoint, and
+/// reports `JobCancelled` itself.
+async fn model_cancel(
+    register_map: &ModelRegister,
+    jobs: &JobRegister,
+    instruction: instruction::Cancel,
+) -> Result<(), ActorError> {
+    let owner = instruction.owner();
+    let (task_id, job_owner, job_model_type) = jobs
+        .get(&instruction.job_id)
+        .and_then(|job| {
+            job.source
+                .task_id()
+                .map(|task_id| (task_id, job.source.owner(), job.source.model_type()))
+        })
+        .ok_or(ActorError::SupervisorModelCancelJobIdNotFound {
+            owner: owner.clone(),
+        })?;
+    if !instruction.role.may(
+        CommandType::Cancel,
+        instruction.model_type(),
+        &owner,
+        &job_owner,
+    ) {
+        return Err(ActorError::SupervisorModelCancelForbidden { owner });
+    }
+    if job_model_type != Some(instruction.model_type()) {
+        return Err(ActorError::SupervisorModelCancelModelTypeMismatch {
+            owner,
+            model_type: instruction.model_type(),
+        });
+    }
+
+    let register = register_map
+        .get(&task_id)
+        .ok_or(ActorError::SupervisorModelCancelTaskIdNotFound { owner })?;
+    register.control.cancel(&instruction.job_id);
+    Ok(())
+}
+
 async fn health_signal(
     tx: &broadcast::Sender<Message>,
     registers: ModelRegister,
+    metrics: MetricsRegister,
+    jobs: JobRegister,
 ) -> Result<(), ActorError> {
     let tx = tx.clone();
     tokio::task::Builder::new()
         .name("health")
         .spawn(async move {
             loop {
-                let value = serde_json::json!(MapJson::from(registers.clone()));
-                if let Err(err) = MessageType::Health.emit(
-                    &tx,
-                    EmitSource::health(tokio::task::id()),
-                    Some(&value.to_string()),
-                ) {
+                let report = metrics::report(&registers, &metrics, &jobs);
+                if let Err(err) = emit::health(&tx, EmitSource::health(tokio::task::id()), report) {
                     println!("{err:#?}");
                 }
                 shared::tools::wait(constants::time::INTERVAL).await;
@@ -144,14 +340,37 @@ async fn model_spawn(
     model_register: &mut ModelRegister,
     supervisor_tx: broadcast::Sender<Message>,
     instruction: instruction::Spawn,
-) -> Result<(), ActorError> {
+    restarts: u32,
+    settings: &Settings,
+) -> Result<String, ActorError> {
     let owner = instruction.owner();
-    let (tx, rx) = mpsc::channel::<Box<dyn Instruction>>(constants::chan::MPSC_LEN);
+    if !instruction.role.may(
+        CommandType::Spawn,
+        instruction.model_type(),
+        &owner,
+        &instruction.owner,
+    ) {
+        return Err(ActorError::SupervisorModelSpawnForbidden { owner });
+    }
+    let batching = settings.batching;
+    let (tx, rx) = mpsc::channel::<Box<dyn Instruction>>(settings.worker_channel);
+    let (control, checkpoint) = Control::new();
     let model_count = model_register
         .iter()
         .filter(|value| value.value().model_type == instruction.model_type())
         .count();
 
+    let device = placement::place(
+        model_register,
+        models::device::count(),
+        instruction.model_type(),
+        instruction.device,
+    )
+    .ok_or(ActorError::SupervisorModelSpawnDeviceNotFound {
+        owner: owner.clone(),
+        device: instruction.device,
+    })?;
+
     let model_type_clone = instruction.model_type();
     let boxed_instruction: Box<dyn Instruction> = Box::new(instruction.clone());
     if let Ok(handle) = tokio::task::Builder::new()
@@ -159,65 +378,171 @@ async fn model_spawn(
         .spawn(async move {
             let source: EmitSource = boxed_instruction.into();
             match model_type_clone {
-                ModelType::Sentiment => sentiment::run(rx, supervisor_tx, source).await,
-                ModelType::Summarize => summarize::run(rx, supervisor_tx, source).await,
-                ModelType::Translation => translation::run(rx, supervisor_tx, source).await,
-                ModelType::Diffusion => diffusion::run(rx, supervisor_tx, source).await,
-                ModelType::Llama => llama::run(rx, supervisor_tx, source).await,
+                ModelType::Sentiment => {
+                    worker::batch::run::<Sentiment>(
+                        rx,
+                        supervisor_tx,
+                        source,
+                        checkpoint,
+                        device,
+                        batching,
+                    )
+                    .await
+                }
+                ModelType::Summarize => {
+                    worker::batch::run::<Summarize>(
+                        rx,
+                        supervisor_tx,
+                        source,
+                        checkpoint,
+                        device,
+                        batching,
+                    )
+                    .await
+                }
+                ModelType::Translation => {
+                    worker::batch::run::<Translation>(
+                        rx,
+                        supervisor_tx,
+                        source,
+                        checkpoint,
+                        device,
+                        batching,
+                    )
+                    .await
+                }
+                ModelType::Diffusion => {
+                    worker::run::<Diffusion>(rx, supervisor_tx, source, checkpoint, device).await
+                }
+                ModelType::Llama => {
+                    worker::run::<Llama>(rx, supervisor_tx, source, checkpoint, device).await
+                }
             }
         })
     {
+        let task_id = handle.id().to_string();
         model_register
             .insert(
-                handle.id().to_string(),
+                task_id.clone(),
                 Register {
                     handle,
                     tx,
+                    control,
                     model_type: instruction.model_type(),
                     owner: instruction.owner,
+                    device,
+                    restarts,
+                    last_used: Instant::now(),
                 },
             )
             .map(|_| Err(ActorError::SupervisorModelSpawnInsertTaskIdFailed { owner }))
-            .unwrap_or(Ok::<(), ActorError>(()))
+            .unwrap_or(Ok(task_id))
     } else {
         Err(ActorError::SupervisorModelSpawnModelSpawnFailed { owner })
     }
 }
 
+/// Runs until every command sender is dropped, then shuts the workers down.
+/// A new value on `settings` applies to the commands that follow, and the
+/// workers are spawned or retired to match its preload set.
 pub async fn run(
     tx: broadcast::Sender<Message>,
     mut rx: mpsc::Receiver<Command>,
-    models: Vec<ModelType>,
+    mut settings: watch::Receiver<Settings>,
 ) {
     let mut model_register: ModelRegister = Arc::new(DashMap::new());
+    let jobs: JobRegister = Arc::new(DashMap::new());
+    let metrics: MetricsRegister = Arc::new(DashMap::new());
 
-    if let Err(err) = health_signal(&tx, model_register.clone()).await {
+    if let Err(err) = metrics::tracker(&tx, metrics.clone()).await {
         println!("{err}");
         std::process::exit(1);
     }
 
-    for model in models {
-        if let Err(err) = model_spawn(
-            &mut model_register,
-            tx.clone(),
-            instruction::Spawn::new(constants::role::ROOT, model),
-        )
-        .await
-        {
-            println!("{err}");
-            std::process::exit(1);
+    if let Err(err) = health_signal(&tx, model_register.clone(), metrics, jobs.clone()).await {
+        println!("{err}");
+        std::process::exit(1);
+    }
+
+    if let Err(err) = job::tracker(&tx, jobs.clone(), model_register.clone()).await {
+        println!("{err}");
+        std::process::exit(1);
+    }
+
+    if let Err(err) = queue::position_signal(&tx, model_register.clone(), jobs.clone()).await {
+        println!("{err}");
+        std::process::exit(1);
+    }
+
+    if let Err(err) =
+        watchdog::watchdog(&tx, model_register.clone(), jobs.clone(), settings.clone()).await
+    {
+        println!("{err}");
+        std::process::exit(1);
+    }
+
+    if let Err(err) =
+        residency::evictor(&tx, model_register.clone(), jobs.clone(), settings.clone()).await
+    {
+        println!("{err}");
+        std::process::exit(1);
+    }
+
+    let mut current = settings.borrow_and_update().clone();
+    for preload in current.preload.iter() {
+        for _ in 0..preload.replicas {
+            let spawn = instruction::Spawn::new(constants::role::ROOT, preload.model_type)
+                .with_device(preload.device);
+            if let Err(err) = model_spawn(&mut model_register, tx.clone(), spawn, 0, &current).await
+            {
+                println!("{err}");
+                std::process::exit(1);
+            }
         }
     }
 
-    while let Some(command) = rx.recv().await {
-        let source = EmitSource::from(command.boxed_instruction()).set_task_id(tokio::task::id());
+    let mut reloading = true;
+    loop {
+        let command = tokio::select! {
+            command = rx.recv() => command,
+            changed = settings.changed(), if reloading => {
+                if changed.is_err() {
+                    reloading = false;
+                    continue;
+                }
+                let previous = current;
+                current = settings.borrow_and_update().clone();
+                if previous.preload != current.preload {
+                    reload::reconcile(&mut model_register, &jobs, &tx, &previous, &current).await;
+                }
+                continue;
+            }
+        };
+        let Some(command) = command else {
+            break;
+        };
+
+        // Verdicts name the worker a command targets, the supervisor otherwise.
+        let source = EmitSource::from(command.boxed_instruction());
+        let source = match source.task_id() {
+            Some(_) => source,
+            None => source.set_task_id(tokio::task::id()),
+        };
         let response: Result<(), ActorError> = match command {
-            Command::Kill(instruction) => model_kill(&mut model_register, &tx, instruction).await,
+            Command::Kill(instruction) => {
+                model_kill(&mut model_register, &jobs, &tx, instruction).await
+            }
             Command::Spawn(instruction) => {
-                model_spawn(&mut model_register, tx.clone(), instruction).await
+                model_spawn(&mut model_register, tx.clone(), instruction, 0, &current)
+                    .await
+                    .map(|_| ())
+            }
+            Command::Process(instruction) => {
+                model_process(&model_register, &jobs, &tx, instruction, &current).await
             }
-            Command::Process(instruction) => model_process(&model_register, instruction).await,
-            _ => Err(ActorError::SupervisoRunCommandNotImplemented),
+            Command::Pause(instruction) => model_pause(&model_register, &tx, instruction).await,
+            Command::Resume(instruction) => model_resume(&model_register, &tx, instruction).await,
+            Command::Cancel(instruction) => model_cancel(&model_register, &jobs, instruction).await,
         };
 
         match response {
@@ -237,4 +562,6 @@ pub async fn run(
             }
         };
     }
+
+    shutdown::shutdown(&model_register, &jobs, &tx, constants::time::SHUTDOWN).await;
 }
@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+
+use shared::types::{Device, ModelType};
+
+use super::ModelRegister;
+
+pub(crate) type Inventory = BTreeMap<Device, Vec<ModelType>>;
+
+/// Models run by the workers on each device: the CPU, then every GPU by
+/// index, idle ones included.
+pub(crate) fn inventory(registers: &ModelRegister, gpus: usize) -> Inventory {
+    let mut inventory: Inventory = std::iter::once(Device::Cpu)
+        .chain((0..gpus).map(Device::Cuda))
+        .map(|device| (device, vec![]))
+        .collect();
+
+    for register in registers.iter() {
+        if let Some(workers) = inventory.get_mut(&register.device) {
+            workers.push(register.model_type);
+        }
+    }
+    inventory
+}
+
+/// Resolves the device hint of a spawn, `None` when it names a missing GPU.
+pub(crate) fn place(
+    registers: &ModelRegister,
+    gpus: usize,
+    model_type: ModelType,
+    hint: Device,
+) -> Option<Device> {
+    match hint {
+        Device::Cpu => Some(Device::Cpu),
+        Device::Cuda(index) => (index < gpus).then_some(hint),
+        Device::Auto => Some(auto(&inventory(registers, gpus), model_type)),
+    }
+}
+
+/// Heavy models get a GPU of their own and fall back to the CPU once every
+/// GPU holds one; light models join the GPU with the fewest heavy models,
+/// then the fewest workers. Ties go to the lowest index, and a host without
+/// GPU places everything on the CPU.
+fn auto(inventory: &Inventory, model_type: ModelType) -> Device {
+    let heavy = |workers: &[ModelType]| workers.iter().filter(|model| model.is_heavy()).count();
+    let gpus = inventory
+        .iter()
+        .filter(|(device, _)| matches!(device, Device::Cuda(_)));
+
+    let gpu = if model_type.is_heavy() {
+        gpus.filter(|(_, workers)| heavy(workers) == 0)
+            .min_by_key(|(_, workers)| workers.len())
+    } else {
+        gpus.min_by_key(|(_, workers)| (heavy(workers), workers.len()))
+    };
+    gpu.map_or(Device::Cpu, |(device, _)| *device)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{auto, place, Inventory};
+    use crate::supervisor::ModelRegister;
+    use shared::types::{Device, ModelType};
+
+    fn inventory(workers: &[(Device, &[ModelType])]) -> Inventory {
+        workers
+            .iter()
+            .map(|(device, models)| (*device, models.to_vec()))
+            .collect()
+    }
+
+    #[test]
+    fn no_gpu() {
+        let cpu = inventory(&[(Device::Cpu, &[ModelType::Llama])]);
+        assert_eq!(auto(&cpu, ModelType::Diffusion), Device::Cpu);
+        assert_eq!(auto(&cpu, ModelType::Sentiment), Device::Cpu);
+
+        let registers = ModelRegister::default();
+        assert_eq!(
+            place(&registers, 0, ModelType::Llama, Device::Auto),
+            Some(Device::Cpu)
+        );
+        assert_eq!(
+            place(&registers, 0, ModelType::Llama, Device::Cuda(0)),
+            None
+        );
+    }
+
+    #[test]
+    fn heavy() {
+        let empty = inventory(&[
+            (Device::Cpu, &[]),
+            (Device::Cuda(0), &[]),
+            (Device::Cuda(1), &[]),
+        ]);
+        assert_eq!(auto(&empty, ModelType::Llama), Device::Cuda(0));
+
+        let one = inventory(&[
+            (Device::Cpu, &[]),
+            (Device::Cuda(0), &[ModelType::Llama]),
+            (Device::Cuda(1), &[ModelType::Sentiment]),
+        ]);
+        assert_eq!(auto(&one, ModelType::Diffusion), Device::Cuda(1));
+
+        let full = inventory(&[
+            (Device::Cpu, &[]),
+            (Device::Cuda(0), &[ModelType::Llama]),
+            (Device::Cuda(1), &[ModelType::Diffusion]),
+        ]);
+        assert_eq!(auto(&full, ModelType::Llama), Device::Cpu);
+    }
+
+    #[test]
+    fn light() {
+        let gpus = inventory(&[
+            (Device::Cpu, &[]),
+            (Device::Cuda(0), &[ModelType::Llama]),
+            (
+                Device::Cuda(1),
+                &[ModelType::Sentiment, ModelType::Summarize],
+            ),
+            (Device::Cuda(2), &[ModelType::Translation]),
+        ]);
+        assert_eq!(auto(&gpus, ModelType::Sentiment), Device::Cuda(2));
+
+        let heavy = inventory(&[
+            (Device::Cpu, &[]),
+            (Device::Cuda(0), &[ModelType::Llama]),
+            (Device::Cuda(1), &[ModelType::Diffusion]),
+        ]);
+        assert_eq!(auto(&heavy, ModelType::Summarize), Device::Cuda(0));
+    }
+
+    #[test]
+    fn explicit() {
+        let registers = ModelRegister::default();
+        assert_eq!(
+            place(&registers, 2, ModelType::Llama, Device::Cuda(1)),
+            Some(Device::Cuda(1))
+        );
+        assert_eq!(
+            place(&registers, 2, ModelType::Llama, Device::Cuda(2)),
+            None
+        );
+        assert_eq!(
+            place(&registers, 2, ModelType::Llama, Device::Cpu),
+            Some(Device::Cpu)
+        );
+        assert_eq!(
+            place(&registers, 2, ModelType::Sentiment, Device::Auto),
+            Some(Device::Cuda(0))
+        );
+    }
+}
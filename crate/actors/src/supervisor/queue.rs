@@ -0,0 +1,58 @@
+use tokio::sync::broadcast;
+
+use shared::{
+    constants,
+    message::{emit, Message},
+    tools::root,
+};
+
+use super::job::JobRegister;
+use super::ModelRegister;
+use crate::error::ActorError;
+
+/// Ids of the jobs waiting on the given worker, oldest first.
+pub(crate) fn waiting(jobs: &JobRegister, task_id: &str) -> Vec<String> {
+    let mut waiting: Vec<_> = jobs
+        .iter()
+        .filter(|job| job.value().is_waiting_on(task_id))
+        .map(|job| (job.value().queued_at, job.key().clone()))
+        .collect();
+    waiting.sort();
+    waiting.into_iter().map(|(_, job_id)| job_id).collect()
+}
+
+pub(crate) fn is_full(jobs: &JobRegister, task_id: &str, capacity: usize) -> bool {
+    waiting(jobs, task_id).len() >= capacity
+}
+
+/// Periodically tells every queued job its rank on its worker.
+pub(crate) async fn position_signal(
+    tx: &broadcast::Sender<Message>,
+    registers: ModelRegister,
+    jobs: JobRegister,
+) -> Result<(), ActorError> {
+    let tx = tx.clone();
+    tokio::task::Builder::new()
+        .name("queue")
+        .spawn(async move {
+            loop {
+                let task_ids: Vec<String> =
+                    registers.iter().map(|value| value.key().clone()).collect();
+                for task_id in task_ids {
+                    for (index, job_id) in waiting(&jobs, &task_id).iter().enumerate() {
+                        let Some(source) = jobs.get(job_id).map(|job| job.source.clone()) else {
+                            continue;
+                        };
+                        if let Err(err) = emit::queue_position(&tx, source, index + 1) {
+                            println!("{err:#?}");
+                        }
+                    }
+                }
+                shared::tools::wait(constants::time::INTERVAL).await;
+            }
+        })
+        .and(Ok(()))
+        .or(Err(ActorError::SupervisorQueueSignalSpawnFailed {
+            owner: root(),
+        }))
+}
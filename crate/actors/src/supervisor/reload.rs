@@ -0,0 +1,97 @@
+use std::{collections::HashSet, time::Instant};
+use tokio::sync::broadcast;
+
+use shared::{command::instruction, constants, message::Message, types::ModelType};
+
+use super::job::JobRegister;
+use super::{dispatch, model_spawn, residency, ModelRegister, Settings};
+
+/// Root workers of the model, with their jobs in flight and last use.
+fn root_workers(
+    registers: &ModelRegister,
+    jobs: &JobRegister,
+    model_type: ModelType,
+) -> Vec<(usize, Instant, String)> {
+    registers
+        .iter()
+        .filter(|register| register.value().owner == constants::role::ROOT)
+        .filter(|register| register.value().model_type == model_type)
+        .map(|register| {
+            (
+                dispatch::depth(jobs, register.key()),
+                register.value().last_used,
+                register.key().clone(),
+            )
+        })
+        .collect()
+}
+
+/// Spawns or retires root workers so that each model type gets the replicas
+/// the new preload set asks for, counted against the root workers actually
+/// registered. Workers spawned by users are left alone. Only idle workers are
+/// retired, least recently used first; busy ones are kept. With lazy
+/// residency, only the replicas added to the preload set are spawned.
+pub(crate) async fn reconcile(
+    registers: &mut ModelRegister,
+    jobs: &JobRegister,
+    tx: &broadcast::Sender<Message>,
+    previous: &Settings,
+    settings: &Settings,
+) {
+    let model_types: HashSet<ModelType> = previous
+        .preload
+        .iter()
+        .chain(settings.preload.iter())
+        .map(|preload| preload.model_type)
+        .collect();
+
+    for model_type in model_types {
+        let workers = root_workers(registers, jobs, model_type);
+        let before = workers.len();
+        let after = settings.replicas(model_type);
+
+        let mut missing = after.saturating_sub(before);
+        if settings.residency.lazy {
+            missing = missing.min(after.saturating_sub(previous.replicas(model_type)));
+        }
+        if missing > 0 {
+            let device = settings
+                .preload
+                .iter()
+                .rfind(|preload| preload.model_type == model_type)
+                .map(|preload| preload.device)
+                .unwrap_or_default();
+            for _ in 0..missing {
+                let spawn =
+                    instruction::Spawn::new(constants::role::ROOT, model_type).with_device(device);
+                if let Err(err) = model_spawn(registers, tx.clone(), spawn, 0, settings).await {
+                    println!("{err}");
+                }
+            }
+        }
+
+        let excess = before.saturating_sub(after);
+        if excess > 0 {
+            let mut idle: Vec<(Instant, String)> = workers
+                .into_iter()
+                .filter(|(depth, _, task_id)| {
+                    *depth == 0
+                        && registers
+                            .get(task_id)
+                            .is_some_and(|register| !register.control.is_paused())
+                })
+                .map(|(_, last_used, task_id)| (last_used, task_id))
+                .collect();
+            idle.sort();
+            if idle.len() < excess {
+                println!(
+                    "{model_type:?}; {} busy root workers kept over the preload set",
+                    excess - idle.len()
+                );
+            }
+            for (_, task_id) in idle.into_iter().take(excess) {
+                residency::evict(tx, registers, jobs, &task_id, "retired by reload");
+            }
+        }
+    }
+}
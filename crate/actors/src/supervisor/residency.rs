@@ -0,0 +1,97 @@
+use std::time::Instant;
+use tokio::sync::{broadcast, watch};
+
+use shared::{
+    command::instruction::{self, Instruction},
+    constants,
+    message::{
+        emit::{Emit, EmitSource},
+        Message,
+    },
+    tools::root,
+    types::MessageType,
+};
+
+use super::job::{self, JobRegister};
+use super::{dispatch, ModelRegister, Settings};
+use crate::error::ActorError;
+
+/// Workers with no job in flight and not paused, with their last use.
+fn idle(registers: &ModelRegister, jobs: &JobRegister) -> Vec<(Instant, String)> {
+    registers
+        .iter()
+        .filter(|register| !register.value().control.is_paused())
+        .filter(|register| dispatch::depth(jobs, register.key()) == 0)
+        .map(|register| (register.value().last_used, register.key().clone()))
+        .collect()
+}
+
+/// Unloads the worker and reports `ModelEvicted`. A job routed to it since
+/// it was found idle is failed rather than left waiting forever.
+pub(super) fn evict(
+    tx: &broadcast::Sender<Message>,
+    registers: &ModelRegister,
+    jobs: &JobRegister,
+    task_id: &str,
+    reason: &str,
+) {
+    let Some((_, register)) = registers.remove(task_id) else {
+        return;
+    };
+    register.handle.abort();
+
+    for job in job::drain(jobs, task_id) {
+        if let Err(err) = MessageType::JobFailed.emit(tx, job.source, Some("Model evicted")) {
+            println!("{err:#?}");
+        }
+    }
+
+    let spawn = instruction::Spawn::new(&register.owner, register.model_type);
+    let boxed_instruction: Box<dyn Instruction> = Box::new(spawn);
+    let source = EmitSource::from(boxed_instruction).set_task_id(register.handle.id());
+    if let Err(err) = MessageType::ModelEvicted.emit(tx, source, Some(reason)) {
+        println!("{err:#?}");
+    }
+}
+
+/// Unloads the workers left idle past the idle timeout, then the least
+/// recently used idle one while the process is over its memory budget. One
+/// worker at most is evicted for memory per round, so the allocator gets to
+/// give the memory back before the budget is checked again.
+pub(crate) async fn evictor(
+    tx: &broadcast::Sender<Message>,
+    registers: ModelRegister,
+    jobs: JobRegister,
+    settings: watch::Receiver<Settings>,
+) -> Result<(), ActorError> {
+    let tx = tx.clone();
+    tokio::task::Builder::new()
+        .name("evictor")
+        .spawn(async move {
+            loop {
+                let residency = settings.borrow().residency;
+                let mut idle = idle(&registers, &jobs);
+                idle.sort();
+
+                let (expired, idle): (Vec<_>, Vec<_>) =
+                    idle.into_iter().partition(|(last_used, _)| {
+                        residency.is_expired(last_used.elapsed().as_millis() as u64)
+                    });
+                for (_, task_id) in expired {
+                    evict(&tx, &registers, &jobs, &task_id, "idle timeout");
+                }
+
+                if residency.is_over_budget(shared::tools::resident_memory()) {
+                    if let Some((_, task_id)) = idle.first() {
+                        evict(&tx, &registers, &jobs, task_id, "memory budget");
+                    }
+                }
+
+                shared::tools::wait(constants::time::WATCHDOG).await;
+            }
+        })
+        .and(Ok(()))
+        .or(Err(ActorError::SupervisorEvictorSpawnFailed {
+            owner: root(),
+        }))
+}
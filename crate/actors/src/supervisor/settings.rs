@@ -0,0 +1,59 @@
+use shared::{
+    config::Application,
+    constants::chan,
+    types::{Batching, ModelType, Preload, Residency},
+};
+
+use super::RestartPolicies;
+
+/// The part of the application configuration the supervisor runs with.
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub preload: Vec<Preload>,
+    pub queue_len: usize,
+    pub worker_channel: usize,
+    pub restart_policies: RestartPolicies,
+    pub batching: Batching,
+    pub residency: Residency,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            preload: Vec::new(),
+            queue_len: chan::QUEUE_LEN,
+            worker_channel: chan::MPSC_LEN,
+            restart_policies: RestartPolicies::default(),
+            batching: Batching::default(),
+            residency: Residency::default(),
+        }
+    }
+}
+
+impl From<&Application> for Settings {
+    fn from(application: &Application) -> Self {
+        Self {
+            preload: application.preload.clone(),
+            queue_len: application.channel.queue_size,
+            worker_channel: application.channel.mpsc_size,
+            restart_policies: application
+                .preload
+                .iter()
+                .filter_map(|preload| preload.restart.map(|policy| (preload.model_type, policy)))
+                .collect(),
+            batching: application.batching,
+            residency: application.residency,
+        }
+    }
+}
+
+impl Settings {
+    /// Workers to keep loaded for the model, summed over its preload entries.
+    pub fn replicas(&self, model_type: ModelType) -> usize {
+        self.preload
+            .iter()
+            .filter(|preload| preload.model_type == model_type)
+            .map(|preload| preload.replicas)
+            .sum()
+    }
+}
@@ -0,0 +1,72 @@
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+use shared::{
+    command::instruction::{self, Instruction},
+    constants,
+    message::{emit::Emit, Message},
+    tools::root,
+    types::MessageType,
+};
+
+use super::job::{self, JobRegister};
+use super::ModelRegister;
+
+/// Stops the workers once no more commands can come in. Jobs already accepted
+/// are given `grace` milliseconds to finish; the ones still there after that
+/// are cancelled and get `time::SHUTDOWN_CANCEL` milliseconds to reach their
+/// next checkpoint. Only the workers still holding a job then are aborted,
+/// the others see their queue closed. Every worker is reported with
+/// `ModelKilled`.
+pub(crate) async fn shutdown(
+    registers: &ModelRegister,
+    jobs: &JobRegister,
+    tx: &broadcast::Sender<Message>,
+    grace: u64,
+) {
+    let deadline = Instant::now() + Duration::from_millis(grace);
+    while !jobs.is_empty() && Instant::now() < deadline {
+        shared::tools::wait(constants::time::WATCHDOG).await;
+    }
+
+    for register in registers.iter() {
+        let job_ids = jobs
+            .iter()
+            .filter(|job| job.value().is_on(register.key()))
+            .map(|job| job.key().clone())
+            .collect::<Vec<_>>();
+        for job_id in job_ids {
+            register.control.cancel(&job_id);
+        }
+    }
+    let deadline = Instant::now() + Duration::from_millis(constants::time::SHUTDOWN_CANCEL);
+    while !jobs.is_empty() && Instant::now() < deadline {
+        shared::tools::wait(constants::time::WATCHDOG).await;
+    }
+
+    let task_ids: Vec<String> = registers.iter().map(|value| value.key().clone()).collect();
+    for task_id in task_ids {
+        let Some((_, register)) = registers.remove(&task_id) else {
+            continue;
+        };
+
+        let stuck = job::drain(jobs, &task_id);
+        drop(register.tx);
+        if !stuck.is_empty() {
+            register.handle.abort();
+        }
+        for job in stuck {
+            if let Err(err) =
+                MessageType::JobFailed.emit(tx, job.source, Some("Server shutting down"))
+            {
+                println!("{err:#?}");
+            }
+        }
+
+        let kill = instruction::Kill::new(&root(), register.model_type, &task_id);
+        let boxed_instruction: Box<dyn Instruction> = Box::new(kill);
+        if let Err(err) = MessageType::ModelKilled.emit(tx, boxed_instruction.into(), None) {
+            println!("{err:#?}");
+        }
+    }
+}
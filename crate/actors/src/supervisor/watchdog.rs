@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use tokio::{
+    sync::{broadcast, watch},
+    task::JoinError,
+};
+
+use shared::{
+    command::instruction::{self, Instruction},
+    constants,
+    message::{
+        emit::{Emit, EmitSource},
+        Message,
+    },
+    tools::root,
+    types::{MessageType, ModelType, RestartPolicy},
+};
+
+use super::job::{self, JobRegister};
+use super::{model_spawn, ModelRegister, Register, Settings};
+use crate::error::ActorError;
+
+pub type RestartPolicies = HashMap<ModelType, RestartPolicy>;
+
+/// Why a finished worker stopped, `None` when it was not a crash.
+fn crash_reason(result: Result<Result<(), String>, JoinError>) -> Option<String> {
+    match result {
+        Ok(Ok(())) => None,
+        Ok(Err(error)) => Some(error),
+        Err(error) if error.is_panic() => {
+            let payload = error.into_panic();
+            payload
+                .downcast_ref::<&str>()
+                .map(|value| value.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .or(Some("unknown panic".to_owned()))
+        }
+        Err(_) => None,
+    }
+}
+
+/// Removes finished workers from the register. Crashed ones are reported with
+/// `ModelCrashed`, their jobs are failed and they are spawned again once
+/// their model restart policy backoff has elapsed.
+pub(crate) async fn watchdog(
+    tx: &broadcast::Sender<Message>,
+    registers: ModelRegister,
+    jobs: JobRegister,
+    settings: watch::Receiver<Settings>,
+) -> Result<(), ActorError> {
+    let tx = tx.clone();
+    tokio::task::Builder::new()
+        .name("watchdog")
+        .spawn(async move {
+            loop {
+                let finished: Vec<String> = registers
+                    .iter()
+                    .filter(|value| value.value().handle.is_finished())
+                    .map(|value| value.key().clone())
+                    .collect();
+
+                for task_id in finished {
+                    let Some((_, register)) = registers.remove(&task_id) else {
+                        continue;
+                    };
+                    let Register {
+                        handle,
+                        owner,
+                        model_type,
+                        device,
+                        restarts,
+                        ..
+                    } = register;
+
+                    let spawn = instruction::Spawn::new(&owner, model_type).with_device(device);
+                    let boxed_instruction: Box<dyn Instruction> = Box::new(spawn.clone());
+                    let source = EmitSource::from(boxed_instruction).set_task_id(handle.id());
+                    let Some(reason) = crash_reason(handle.await) else {
+                        continue;
+                    };
+
+                    for job in job::drain(&jobs, &task_id) {
+                        if let Err(err) =
+                            MessageType::JobFailed.emit(&tx, job.source, Some("Model crashed"))
+                        {
+                            println!("{err:#?}");
+                        }
+                    }
+                    if let Err(err) = MessageType::ModelCrashed.emit(&tx, source, Some(&reason)) {
+                        println!("{err:#?}");
+                    }
+
+                    let settings = settings.borrow().clone();
+                    let policy = settings
+                        .restart_policies
+                        .get(&model_type)
+                        .copied()
+                        .unwrap_or_default();
+                    if !policy.allows(restarts) {
+                        println!("{model_type:?} crashed {restarts} times, not restarting");
+                        continue;
+                    }
+                    restart(
+                        &tx,
+                        &registers,
+                        spawn,
+                        restarts + 1,
+                        policy.delay(restarts),
+                        settings,
+                    );
+                }
+
+                shared::tools::wait(constants::time::WATCHDOG).await;
+            }
+        })
+        .and(Ok(()))
+        .or(Err(ActorError::SupervisorWatchdogSpawnFailed {
+            owner: root(),
+        }))
+}
+
+fn restart(
+    tx: &broadcast::Sender<Message>,
+    registers: &ModelRegister,
+    spawn: instruction::Spawn,
+    restarts: u32,
+    delay: u64,
+    settings: Settings,
+) {
+    let tx = tx.clone();
+    let mut registers = registers.clone();
+    let name = format!("restart-{:?}", spawn.model_type);
+    let spawned = tokio::task::Builder::new().name(&name).spawn(async move {
+        shared::tools::wait(delay).await;
+        if let Err(err) = model_spawn(&mut registers, tx, spawn, restarts, &settings).await {
+            println!("{err}");
+        }
+    });
+
+    if let Err(err) = spawned {
+        println!("{err:#?}");
+    }
+}
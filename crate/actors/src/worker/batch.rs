@@ -0,0 +1,117 @@
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+use tokio::time::Instant;
+
+use shared::command::instruction::Instruction;
+use shared::control::Checkpoint;
+use shared::message::{
+    emit::{Emit, EmitSource},
+    Message,
+};
+use shared::types::{Batching, Device, MessageType};
+
+use super::{control, load, parse, report, Worker};
+
+/// A model able to run several queued inputs in one forward pass.
+pub trait Batch: Worker {
+    /// Returns one output per input, in the same order.
+    fn predict_batch(&mut self, inputs: Vec<Self::Input>) -> Vec<Self::Output>;
+}
+
+/// Waits for a first `Process` instruction, then gathers the ones queued
+/// behind it for at most `max_wait` milliseconds. Control instructions are
+/// consumed on the way. Returns `None` once the supervisor dropped the channel.
+async fn collect(
+    rx: &mut mpsc::Receiver<Box<dyn Instruction>>,
+    checkpoint: &mut Checkpoint,
+    batching: Batching,
+) -> Option<Vec<Box<dyn Instruction>>> {
+    let first = loop {
+        let instruction = rx.recv().await?;
+        if !control::handle(instruction.as_ref(), checkpoint).await {
+            break instruction;
+        }
+    };
+
+    let mut batch = vec![first];
+    let deadline = Instant::now() + Duration::from_millis(batching.max_wait);
+    while batch.len() < batching.max_size() {
+        match tokio::time::timeout_at(deadline, rx.recv()).await {
+            Ok(Some(instruction)) => {
+                if !control::handle(instruction.as_ref(), checkpoint).await {
+                    batch.push(instruction);
+                }
+            }
+            Ok(None) | Err(_) => break,
+        }
+    }
+    Some(batch)
+}
+
+/// Same lifecycle as `super::run`, each job still reports under its own owner
+/// and job id, but the inputs collected together share a single prediction.
+pub async fn run<W: Batch>(
+    mut rx: mpsc::Receiver<Box<dyn Instruction>>,
+    tx: broadcast::Sender<Message>,
+    source: EmitSource,
+    mut checkpoint: Checkpoint,
+    device: Device,
+    batching: Batching,
+) -> Result<(), String> {
+    let source = source.set_task_id(tokio::task::id());
+    let mut model = load::<W>(&tx, &source, device)?;
+
+    while let Some(batch) = collect(&mut rx, &mut checkpoint, batching).await {
+        let mut running = vec![];
+        let mut inputs = vec![];
+        for instruction in batch {
+            let source = source
+                .set_owner(&instruction.owner())
+                .set_job_id(instruction.job_id());
+            let job = checkpoint.with_job_id(instruction.job_id());
+            if control::skip_cancelled(&job, &tx, &source)? {
+                continue;
+            }
+
+            MessageType::JobRunning
+                .emit(&tx, source.clone(), None)
+                .map_err(|err| format!("{err}"))?;
+
+            match parse::<W>(instruction.as_ref()) {
+                Ok(input) => {
+                    running.push((source, job));
+                    inputs.push(input);
+                }
+                Err(error) => {
+                    report(&tx, &source, Err(error))?;
+                    job.release();
+                }
+            }
+        }
+
+        if inputs.is_empty() {
+            continue;
+        }
+
+        let (returned, outputs) = tokio::task::spawn_blocking(move || {
+            let outputs = model.predict_batch(inputs);
+            (model, outputs)
+        })
+        .await
+        .map_err(|err| format!("{err}"))?;
+        model = returned;
+        // A model short of outputs fails the jobs left without one, rather than
+        // leaving them running.
+        let mut outputs = outputs.into_iter();
+        for (source, job) in running {
+            let prediction = match outputs.next() {
+                Some(output) => Ok(Some(output.into())),
+                None => Err("No output for the job in its batch".to_owned()),
+            };
+            report(&tx, &source, prediction)?;
+            job.release();
+        }
+    }
+
+    Ok(())
+}
@@ -1,32 +1,48 @@
-use tokio::sync::mpsc;
+use tokio::sync::broadcast;
 
-use shared::command::instruction::{self, Instruction};
-use shared::constants;
+use shared::command::instruction::Instruction;
+use shared::control::Checkpoint;
+use shared::message::{
+    emit::{Emit, EmitSource},
+    Message,
+};
+use shared::types::{CommandType, MessageType};
 
-pub async fn should_task_pause(rx: &mut mpsc::Receiver<Box<dyn Instruction>>, owner: &str) -> bool {
-    tokio::select! {
-      answer = async {
-        if let Some(message) = rx.recv().await {
-          <dyn instruction::Asking>::is_paused_with_owner(&message, owner)
-        } else {
-          false
-        }
-      } => answer,
-      _ = async {
-        shared::tools::wait(constants::time::VOLATILE).await
-      } => false
+pub fn should_task_pause(instruction: &dyn Instruction) -> bool {
+    instruction.command_type() == CommandType::Pause
+}
+
+pub fn should_task_resume(instruction: &dyn Instruction) -> bool {
+    instruction.command_type() == CommandType::Resume
+}
+
+/// Consumes the control instructions forwarded by the supervisor. A `Pause`
+/// reaching an idle worker holds the loop until the supervisor resumes it, so
+/// the queued `Process` instructions are not started meanwhile.
+/// Returns `true` when the instruction was a control one.
+pub async fn handle(instruction: &dyn Instruction, checkpoint: &mut Checkpoint) -> bool {
+    if should_task_pause(instruction) {
+        checkpoint.resumed().await;
+        true
+    } else {
+        should_task_resume(instruction)
     }
 }
 
-pub async fn should_task_resume(rx: &mut mpsc::Receiver<Box<dyn Instruction>>, owner: &str) {
-    tokio::select! {
-      _ = async {
-        if let Some(message) = rx.recv().await {
-          if <dyn instruction::Asking>::is_resumed_with_owner(&message, owner) {}
-        }
-      } => (),
-      _ = async {
-        shared::tools::wait(constants::time::FOREVER).await
-      } => ()
+/// Drops a job that was cancelled while still queued, reporting `JobCancelled`.
+/// Returns `true` when the worker should skip it.
+pub fn skip_cancelled(
+    checkpoint: &Checkpoint,
+    tx: &broadcast::Sender<Message>,
+    source: &EmitSource,
+) -> Result<bool, String> {
+    if !checkpoint.is_cancelled() {
+        return Ok(false);
     }
+
+    checkpoint.release();
+    MessageType::JobCancelled
+        .emit(tx, source.clone(), None)
+        .map_err(|err| format!("{err}"))?;
+    Ok(true)
 }
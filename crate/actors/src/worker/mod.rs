@@ -1,6 +1,163 @@
+pub mod batch;
 pub mod control;
 pub mod diffusion;
 pub mod llama;
 pub mod sentiment;
 pub mod summarize;
 pub mod translation;
+
+use tokio::sync::{broadcast, mpsc};
+
+use shared::command::instruction::Instruction;
+use shared::control::Checkpoint;
+use shared::message::{
+    emit::{self, Emit, EmitSource, Emitter},
+    Message,
+};
+use shared::schema::{ModelInput, ModelOutput, SchemaError};
+use shared::types::{Device, MessageType};
+
+/// What a job gets besides its input: a way to report progress and the
+/// checkpoint to poll between two steps of a long prediction.
+pub struct Context<'a> {
+    pub emitter: Emitter,
+    pub checkpoint: &'a Checkpoint,
+}
+
+/// A model served by a worker. Adding a model to the supervisor only takes
+/// loading it and running one prediction; the job lifecycle is handled by `run`.
+/// Predictions run on the blocking pool, so models move between threads.
+pub trait Worker: Sized + Send + 'static {
+    type Input: TryFrom<ModelInput, Error = SchemaError> + Send;
+    type Output: Into<ModelOutput> + Send;
+
+    /// Loads the model on the device the supervisor placed the worker on.
+    fn load(device: Device) -> Self;
+
+    fn device_name(&self) -> String;
+
+    /// Returns `None` when the job was cancelled before the prediction ended.
+    fn predict(&mut self, input: Self::Input, context: &Context) -> Option<Self::Output>;
+}
+
+fn parse<W: Worker>(instruction: &dyn Instruction) -> Result<W::Input, String> {
+    let input = instruction
+        .input()
+        .ok_or_else(|| "Missing input".to_owned())?;
+    W::Input::try_from(input).map_err(|err| format!("{err}"))
+}
+
+/// Runs the prediction on the blocking pool and hands the model back. Models
+/// compute synchronously and block on their checkpoint while paused, which
+/// must not hold a thread of the runtime.
+async fn predict<W: Worker>(
+    mut model: W,
+    input: W::Input,
+    emitter: Emitter,
+    checkpoint: Checkpoint,
+) -> Result<(W, Option<W::Output>), String> {
+    tokio::task::spawn_blocking(move || {
+        let context = Context {
+            emitter,
+            checkpoint: &checkpoint,
+        };
+        let output = model.predict(input, &context);
+        (model, output)
+    })
+    .await
+    .map_err(|err| format!("{err}"))
+}
+
+fn load<W: Worker>(
+    tx: &broadcast::Sender<Message>,
+    source: &EmitSource,
+    device: Device,
+) -> Result<W, String> {
+    MessageType::ModelStarted
+        .emit(tx, source.clone(), None)
+        .map_err(|err| format!("{err}"))?;
+
+    let before = shared::tools::resident_memory();
+    let model = W::load(device);
+    let memory = before
+        .zip(shared::tools::resident_memory())
+        .map(|(before, after)| after.saturating_sub(before));
+
+    emit::loaded(tx, source.clone(), model.device_name(), memory)
+        .map_err(|err| format!("{err}"))?;
+    Ok(model)
+}
+
+/// Reports how a job ended: its prediction, a cancellation or an error.
+fn report(
+    tx: &broadcast::Sender<Message>,
+    source: &EmitSource,
+    prediction: Result<Option<ModelOutput>, String>,
+) -> Result<(), String> {
+    match prediction {
+        Ok(Some(output)) => {
+            emit::prediction(tx, source.clone(), output).map_err(|err| format!("{err}"))?;
+            MessageType::JobDone
+                .emit(tx, source.clone(), None)
+                .map_err(|err| format!("{err}"))?;
+        }
+        Ok(None) => {
+            MessageType::JobCancelled
+                .emit(tx, source.clone(), None)
+                .map_err(|err| format!("{err}"))?;
+        }
+        Err(error) => {
+            MessageType::ModelError
+                .emit(tx, source.clone(), Some(&error))
+                .map_err(|err| format!("{err}"))?;
+            MessageType::JobFailed
+                .emit(tx, source.clone(), Some(&error))
+                .map_err(|err| format!("{err}"))?;
+        }
+    }
+    Ok(())
+}
+
+pub async fn run<W: Worker>(
+    mut rx: mpsc::Receiver<Box<dyn Instruction>>,
+    tx: broadcast::Sender<Message>,
+    source: EmitSource,
+    mut checkpoint: Checkpoint,
+    device: Device,
+) -> Result<(), String> {
+    let source = source.set_task_id(tokio::task::id());
+    let mut model = load::<W>(&tx, &source, device)?;
+
+    while let Some(instruction) = rx.recv().await {
+        if control::handle(instruction.as_ref(), &mut checkpoint).await {
+            continue;
+        }
+
+        let source = source
+            .set_owner(&instruction.owner())
+            .set_job_id(instruction.job_id());
+        let job = checkpoint.with_job_id(instruction.job_id());
+        if control::skip_cancelled(&job, &tx, &source)? {
+            continue;
+        }
+
+        MessageType::JobRunning
+            .emit(&tx, source.clone(), None)
+            .map_err(|err| format!("{err}"))?;
+
+        let prediction = match parse::<W>(instruction.as_ref()) {
+            Ok(input) => {
+                let emitter = Emitter::new(tx.clone(), source.clone());
+                let (returned, output) = predict(model, input, emitter, job.clone()).await?;
+                model = returned;
+                Ok(output.map(Into::into))
+            }
+            Err(error) => Err(error),
+        };
+
+        report(&tx, &source, prediction)?;
+        job.release();
+    }
+
+    Ok(())
+}
@@ -1,64 +1,34 @@
 use models::summarize::Summarize;
-use tokio::sync::{broadcast, mpsc};
+use shared::schema::{SummarizeInput, SummarizeOutput};
+use shared::types::Device;
 
-use shared::types::MessageType;
-use shared::{
-    command::instruction::Instruction,
-    message::{
-        emit::{Emit, EmitSource},
-        Message,
-    },
-};
+use super::{batch::Batch, Context, Worker};
 
-#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
-struct Input {
-    input: String,
-}
-
-pub async fn run(
-    mut rx: mpsc::Receiver<Box<dyn Instruction>>,
-    tx: broadcast::Sender<Message>,
-    source: EmitSource,
-) -> Result<(), String> {
-    let source = source.set_task_id(tokio::task::id());
+impl Worker for Summarize {
+    type Input = SummarizeInput;
+    type Output = SummarizeOutput;
 
-    if let Err(err) = MessageType::ModelStarted.emit(&tx, source.clone(), None) {
-        eprintln!("{err:#?}");
+    fn load(device: Device) -> Self {
+        Summarize::new(models::device::resolve(device))
     }
 
-    let model = Summarize::default();
-
-    if let Err(err) = MessageType::ModelLoaded.emit(&tx, source.clone(), None) {
-        eprintln!("{err:#?}");
+    fn device_name(&self) -> String {
+        format!("{:?}", self.device())
     }
 
-    while let Some(command) = rx.recv().await {
-        let owner = command.owner();
-        let source = source.set_owner(&owner);
-
-        let json_str = command.json_input().unwrap();
-        let json_parsing_result: Result<Input, String> =
-            serde_json::from_str(&json_str).map_err(|_| format!("Json Parse Error"));
-
-        match json_parsing_result {
-            Ok(params) => {
-                let input = params.input;
-                let message = model.prediction(&input);
-                if let Err(err) =
-                    MessageType::ModelPrediction.emit(&tx, source.clone(), Some(message.as_str()))
-                {
-                    eprintln!("{err:#?}");
-                }
-            }
-            Err(error) => {
-                if let Err(err) =
-                    MessageType::ModelError.emit(&tx, source.clone(), Some(error.as_str()))
-                {
-                    eprintln!("{err:#?}");
-                }
-            }
-        }
+    fn predict(&mut self, input: SummarizeInput, _context: &Context) -> Option<SummarizeOutput> {
+        Some(SummarizeOutput {
+            summary: self.prediction(&input.input),
+        })
     }
+}
 
-    Ok(())
+impl Batch for Summarize {
+    fn predict_batch(&mut self, inputs: Vec<SummarizeInput>) -> Vec<SummarizeOutput> {
+        let inputs: Vec<&str> = inputs.iter().map(|input| input.input.as_str()).collect();
+        self.predictions(&inputs)
+            .into_iter()
+            .map(|summary| SummarizeOutput { summary })
+            .collect()
+    }
 }
@@ -1,65 +1,68 @@
-use tokio::sync::{broadcast, mpsc};
-
 use models::translation::Translation;
-use shared::command::instruction::Instruction;
-use shared::message::{
-    emit::{Emit, EmitSource},
-    Message,
-};
-use shared::types::MessageType;
+use shared::schema::{TranslationInput, TranslationOutput};
+use shared::types::Device;
 
-#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
-struct Input {
-    input: String,
-    owner: String,
-    source_lang: String,
-    target_lang: String,
-}
+use super::{batch::Batch, Context, Worker};
 
-pub async fn run(
-    mut rx: mpsc::Receiver<Box<dyn Instruction>>,
-    tx: broadcast::Sender<Message>,
-    source: EmitSource,
-) -> Result<(), String> {
-    let source = source.set_task_id(tokio::task::id());
+impl Worker for Translation {
+    type Input = TranslationInput;
+    type Output = TranslationOutput;
 
-    MessageType::ModelStarted
-        .emit(&tx, source.clone(), None)
-        .map_err(|_| format!("Json Parse Error"))?;
+    fn load(device: Device) -> Self {
+        Translation::new(models::device::resolve(device))
+    }
 
-    let model = Translation::default();
+    fn device_name(&self) -> String {
+        format!("{:?}", self.device())
+    }
 
-    MessageType::ModelLoaded
-        .emit(&tx, source.clone(), None)
-        .map_err(|err| format!("{err}"))?;
+    fn predict(
+        &mut self,
+        input: TranslationInput,
+        _context: &Context,
+    ) -> Option<TranslationOutput> {
+        let TranslationInput {
+            input,
+            source_lang,
+            target_lang,
+        } = input;
+        Some(TranslationOutput {
+            translation: self.prediction(&input, &source_lang, &target_lang),
+        })
+    }
+}
 
-    while let Some(instruction) = rx.recv().await {
-        let json_str = instruction.json_input().unwrap();
-        let json_parsing_result: Result<Input, String> =
-            serde_json::from_str(&json_str).map_err(|_| format!("Json Parse Error"));
+impl Batch for Translation {
+    /// The pipeline translates a whole slice into a single language pair, so
+    /// the batch is split by pair and the translations put back in order.
+    fn predict_batch(&mut self, inputs: Vec<TranslationInput>) -> Vec<TranslationOutput> {
+        let mut translations = vec![String::new(); inputs.len()];
+        let mut pending: Vec<usize> = (0..inputs.len()).collect();
+        while let Some(&first) = pending.first() {
+            let TranslationInput {
+                source_lang,
+                target_lang,
+                ..
+            } = &inputs[first];
+            let (pair, rest): (Vec<usize>, Vec<usize>) = pending.into_iter().partition(|&index| {
+                inputs[index].source_lang == *source_lang
+                    && inputs[index].target_lang == *target_lang
+            });
 
-        match json_parsing_result {
-            Ok(params) => {
-                let Input {
-                    input,
-                    owner,
-                    source_lang,
-                    target_lang,
-                } = params;
-                let new_source = source.clone();
-                let new_source = new_source.set_owner(&owner);
-                let message = model.prediction(&input, &source_lang, &target_lang);
-                MessageType::ModelPrediction
-                    .emit(&tx, new_source.clone(), Some(message.as_str()))
-                    .map_err(|err| format!("{err}"))?;
-            }
-            Err(error) => {
-                MessageType::ModelError
-                    .emit(&tx, source.clone(), Some(error.as_str()))
-                    .map_err(|err| format!("{err}"))?;
+            let texts: Vec<&str> = pair
+                .iter()
+                .map(|&index| inputs[index].input.as_str())
+                .collect();
+            let predictions = self.predictions(&texts, source_lang, target_lang);
+            for (index, translation) in pair.into_iter().zip(predictions) {
+                translations[index] = translation;
             }
+            pending = rest;
         }
-    }
 
-    Ok(())
+        translations
+            .into_iter()
+            .map(|translation| TranslationOutput { translation })
+            .collect()
+    }
 }
@@ -1,3 +1,4 @@
+use shared::schema::{ModelInput, SentimentInput, SummarizeInput, TranslationInput};
 use shared::types::ModelType;
 
 pub const SENTIMENT_INPUT: &str = "Probably my all-time favorite movie, a story of selflessness, sacrifice and dedication to a noble cause, but it's not preachy or boring.";
@@ -27,29 +28,25 @@ about exoplanets like K2-18b."];
 pub const TRANSLATION_INPUT: [&str; 1] =
     ["This sentence will be translated in multiple languages."];
 
-#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
-pub struct Input {
-    input: String,
-}
+pub struct Input;
 
 impl Input {
-    pub fn input(model_type: &ModelType) -> String {
+    pub fn input(model_type: &ModelType) -> ModelInput {
         match model_type {
-            ModelType::Sentiment => serde_json::to_string(&Input {
+            ModelType::Sentiment => SentimentInput {
                 input: SENTIMENT_INPUT.to_string(),
-            })
-            .ok()
-            .unwrap(),
-            ModelType::Summarize => serde_json::to_string(&Input {
-                input: serde_json::to_string(&SUMMARZE_INPUT).ok().unwrap(),
-            })
-            .ok()
-            .unwrap(),
-            ModelType::Translation => serde_json::to_string(&Input {
-                input: serde_json::to_string(&TRANSLATION_INPUT).ok().unwrap(),
-            })
-            .ok()
-            .unwrap(),
+            }
+            .into(),
+            ModelType::Summarize => SummarizeInput {
+                input: SUMMARZE_INPUT[0].to_string(),
+            }
+            .into(),
+            ModelType::Translation => TranslationInput {
+                input: TRANSLATION_INPUT[0].to_string(),
+                source_lang: "en".to_owned(),
+                target_lang: "fr".to_owned(),
+            }
+            .into(),
             _ => unimplemented!(),
         }
     }
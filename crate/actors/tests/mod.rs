@@ -3,11 +3,11 @@
 #![feature(async_closure)]
 use actors::supervisor;
 use shared::types::MessageType;
-use shared::types::ModelType;
+use shared::types::{ModelType, Preload};
 use shared::{command::Command, constants, message::Message, tools};
 
 use std::sync::Arc;
-use tokio::sync::{broadcast, mpsc, Mutex};
+use tokio::sync::{broadcast, mpsc, watch, Mutex};
 
 pub mod input;
 use input::Input;
@@ -47,12 +47,14 @@ async fn run() -> Result<(), &'static str> {
     tokio::task::Builder::new()
         .name("supervisor")
         .spawn(async move {
-            supervisor::run(
-                supervisor_tx,
-                supervisor_rx,
-                vec![ModelType::Summarize, ModelType::Sentiment],
-            )
-            .await
+            let (_reload, settings) = watch::channel(supervisor::Settings {
+                preload: vec![
+                    Preload::new(ModelType::Summarize),
+                    Preload::new(ModelType::Sentiment),
+                ],
+                ..Default::default()
+            });
+            supervisor::run(supervisor_tx, supervisor_rx, settings).await
         })
         .map_err(|_| "Cannot spawn the supervisor")?;
     tools::wait(250).await;
@@ -66,7 +68,16 @@ async fn run() -> Result<(), &'static str> {
 
     let current_id = task_id.lock().await.clone().unwrap().clone();
     let input = Input::input(&ModelType::Sentiment);
-    let command = Command::process(&current_id, &input, ModelType::Sentiment);
+    let command = Command::process(&current_id, input);
+    http_tx.send(command).await.map_err(|_| "Cannot send")?;
+    tools::wait(50).await;
+
+    let current_id = task_id.lock().await.clone().unwrap().clone();
+    let command = Command::pause(&current_id, ModelType::Sentiment);
+    http_tx.send(command).await.map_err(|_| "Cannot send")?;
+    tools::wait(50).await;
+
+    let command = Command::resume(&current_id, ModelType::Sentiment);
     http_tx.send(command).await.map_err(|_| "Cannot send")?;
     tools::wait(50).await;
 
@@ -84,7 +95,7 @@ async fn run() -> Result<(), &'static str> {
 
     let current_id = task_id.lock().await.clone().unwrap().clone();
     let input = Input::input(&ModelType::Summarize);
-    let command = Command::process(&current_id, &input, ModelType::Summarize);
+    let command = Command::process(&current_id, input);
     http_tx.send(command).await.map_err(|_| "Cannot send")?;
     tools::wait(50).await;
 
@@ -93,13 +104,31 @@ async fn run() -> Result<(), &'static str> {
     http_tx.send(command).await.map_err(|_| "Cannot send")?;
     tools::wait(50).await;
 
+    /*
+        NEXT TEST DISPATCH
+    */
+    let input = Input::input(&ModelType::Sentiment);
+    let command = Command::dispatch(input);
+    http_tx.send(command).await.map_err(|_| "Cannot send")?;
+    tools::wait(50).await;
+
+    let input = Input::input(&ModelType::Translation);
+    let command = Command::dispatch(input);
+    http_tx.send(command).await.map_err(|_| "Cannot send")?;
+    tools::wait(50).await;
+
     use std::cmp::Ordering::{Equal, Greater};
-    assert!(validate(&messages, &MessageType::CommandFailed, Equal, 0).await);
-    assert!(validate(&messages, &MessageType::CommandSucess, Equal, 6).await);
-    assert!(validate(&messages, &MessageType::ModelPrediction, Equal, 2).await);
+    assert!(validate(&messages, &MessageType::CommandFailed, Equal, 1).await);
+    assert!(validate(&messages, &MessageType::CommandSucess, Equal, 9).await);
+    assert!(validate(&messages, &MessageType::ModelPrediction, Equal, 3).await);
     assert!(validate(&messages, &MessageType::ModelLoaded, Equal, 4).await);
     assert!(validate(&messages, &MessageType::ModelStarted, Equal, 4).await);
     assert!(validate(&messages, &MessageType::ModelKilled, Equal, 2).await);
+    assert!(validate(&messages, &MessageType::ModelPaused, Equal, 1).await);
+    assert!(validate(&messages, &MessageType::ModelResumed, Equal, 1).await);
+    assert!(validate(&messages, &MessageType::JobQueued, Equal, 3).await);
+    assert!(validate(&messages, &MessageType::JobRunning, Equal, 3).await);
+    assert!(validate(&messages, &MessageType::JobDone, Equal, 3).await);
     assert!(validate(&messages, &MessageType::Health, Greater, 1).await);
 
     //println!("{messages:#?}");
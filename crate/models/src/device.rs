@@ -0,0 +1,14 @@
+use shared::types::Device;
+
+/// Number of CUDA devices visible to libtorch, zero on a CPU only host.
+pub fn count() -> usize {
+    tch::Cuda::device_count().max(0) as usize
+}
+
+pub fn resolve(device: Device) -> tch::Device {
+    match device {
+        Device::Cpu => tch::Device::Cpu,
+        Device::Cuda(index) => tch::Device::Cuda(index),
+        Device::Auto => tch::Device::cuda_if_available(),
+    }
+}
@@ -6,10 +6,14 @@ pub mod unet;
 pub mod utils;
 pub mod vae;
 
-use shared::message::Message;
-use tokio::sync::broadcast;
+use shared::control::Checkpoint;
+use shared::message::emit::Emitter;
 
-use crate::diffusion::{pipe::Pipe, text_transformer::Clip, tokenizer::Tokenizer};
+use crate::diffusion::{
+    pipe::{Pipe, PipeConfig},
+    text_transformer::Clip,
+    tokenizer::Tokenizer,
+};
 
 pub struct Diffusion {
     pipe: Pipe,
@@ -29,9 +33,15 @@ pub struct Diffusion {
 
 impl Default for Diffusion {
     fn default() -> Self {
-        let device = tch::Device::cuda_if_available();
+        Self::new(tch::Device::cuda_if_available())
+    }
+}
+
+impl Diffusion {
+    pub fn new(device: tch::Device) -> Self {
         let tokenizer = Tokenizer::create(Default::default()).expect("cannot create tokenizer");
-        let pipe = Pipe::new(Default::default()).expect("cannot create pipe");
+        let config = PipeConfig::default().with_device(device);
+        let pipe = Pipe::new(config).expect("cannot create pipe");
 
         Self {
             pipe,
@@ -39,17 +49,25 @@ impl Default for Diffusion {
             device,
         }
     }
-}
 
-impl Diffusion {
+    pub fn device(&self) -> tch::Device {
+        self.device
+    }
+
+    /// Number of scheduler steps run for each prediction.
+    pub fn steps(&self) -> usize {
+        self.pipe.config.steps
+    }
+
     pub fn prediction(
         &mut self,
         prompt: &str,
         seed: i64,
         height: i64,
         width: i64,
-        tx: Option<broadcast::Sender<Message>>,
-    ) -> Vec<String> {
+        emitter: Option<Emitter>,
+        checkpoint: Option<&Checkpoint>,
+    ) -> Option<Vec<String>> {
         tch::manual_seed(seed);
         let no_grad_guard = tch::no_grad_guard();
         let tensor = tch::Tensor::randn(
@@ -62,7 +80,7 @@ impl Diffusion {
 
         let prediction = self
             .pipe
-            .diffuse(&tensor, &text, true, tx)
+            .diffuse(&tensor, &text, true, emitter, checkpoint)
             .expect("cannot run pipe");
         drop(no_grad_guard);
 
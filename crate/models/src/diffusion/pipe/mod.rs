@@ -1,6 +1,6 @@
-use shared::message::Message;
+use shared::control::Checkpoint;
+use shared::message::emit::Emitter;
 use tch::{Device, Tensor};
-use tokio::sync::broadcast;
 
 use super::unet::model::UNet2DConditionModel;
 use super::unet::schedulers::{select_scheduler, Scheduler};
@@ -88,17 +88,30 @@ impl Pipe {
         init: &Tensor,
         text: &Tensor,
         with_bar: bool,
-        tx: Option<broadcast::Sender<Message>>,
-    ) -> anyhow::Result<Vec<String>> {
+        emitter: Option<Emitter>,
+        checkpoint: Option<&Checkpoint>,
+    ) -> anyhow::Result<Option<Vec<String>>> {
         let Pipe {
             unet, vae, config, ..
         } = self;
 
         let mut images = vec![];
+        let job_id = emitter.as_ref().and_then(Emitter::job_id);
 
         let latent = {
-            self.scheduler
-                .schedule(init, unet, text, config.device, config.steps, with_bar, tx)
+            self.scheduler.schedule(
+                init,
+                unet,
+                text,
+                config.device,
+                config.steps,
+                with_bar,
+                emitter,
+                checkpoint,
+            )
+        };
+        let Some(latent) = latent else {
+            return Ok(None);
         };
 
         let image = {
@@ -108,10 +121,20 @@ impl Pipe {
             (image * 255.).to_kind(tch::Kind::Uint8)
         };
 
-        tch::vision::image::save(&image, format!("images/frame{:04}.png", config.n_frame))?;
-        images.push(format!("images/frame{:04}.png", config.n_frame));
+        // Named after the job, so that workers of the same model never write over
+        // each other; frames of the command line tools are numbered instead.
+        let name = match &job_id {
+            Some(job_id) => format!("{job_id}-{}", images.len()),
+            None => format!("frame{:04}", config.n_frame),
+        };
+        // Saved aside then renamed, so that a stopped process never leaves a truncated frame.
+        let path = format!("images/{name}.png");
+        let partial = format!("images/{name}.partial.png");
+        tch::vision::image::save(&image, &partial)?;
+        std::fs::rename(&partial, &path)?;
+        images.push(path);
         config.inc_n_frame();
 
-        Ok(images)
+        Ok(Some(images))
     }
 }
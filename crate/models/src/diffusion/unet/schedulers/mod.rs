@@ -1,6 +1,6 @@
 use super::model::UNet2DConditionModel;
-use shared::message::emit::Emit;
-use shared::message::Message;
+use shared::control::Checkpoint;
+use shared::message::emit::Emitter;
 use shared::types::MessageType;
 use tch::{Device, IndexOp, Kind, Tensor};
 
@@ -8,7 +8,6 @@ pub mod ddim;
 pub mod integrate;
 pub mod lms_discrete;
 use indicatif::ProgressBar;
-use tokio::sync::broadcast;
 
 use crate::diffusion::unet::schedulers::ddim::DDIMScheduler;
 use crate::diffusion::unet::schedulers::lms_discrete::LMSDiscreteScheduler;
@@ -79,8 +78,9 @@ pub trait Scheduler: Send {
         device: Device,
         steps: usize,
         with_bar: bool,
-        tx: Option<broadcast::Sender<Message>>,
-    ) -> Tensor {
+        emitter: Option<Emitter>,
+        checkpoint: Option<&Checkpoint>,
+    ) -> Option<Tensor> {
         let mut latents = init.shallow_clone();
         latents *= self.init_noise_sigma();
 
@@ -90,13 +90,12 @@ pub trait Scheduler: Send {
             None
         };
 
-        match &tx {
-            Some(tx) => {
+        match &emitter {
+            Some(emitter) => {
                 let message = SchedulerTick { tick: 0, steps };
-                MessageType::SchedulerStep
+                emitter
                     .emit(
-                        tx,
-                        Default::default(),
+                        MessageType::SchedulerStep,
                         Some(&serde_json::json!(message).to_string()),
                     )
                     .map_err(|err| format!("{err}"))
@@ -106,6 +105,15 @@ pub trait Scheduler: Send {
         };
 
         for (index, timestep) in self.timesteps().iter().enumerate() {
+            if let Some(checkpoint) = checkpoint {
+                if !checkpoint.block() {
+                    if let Some(bar) = &bar {
+                        bar.abandon();
+                    }
+                    return None;
+                }
+            }
+
             match &bar {
                 Some(bar) => bar.inc(1),
                 None => (),
@@ -122,16 +130,15 @@ pub trait Scheduler: Send {
 
             latents = self.step(&noise_pred, *timestep, &latents);
 
-            match &tx {
-                Some(tx) => {
+            match &emitter {
+                Some(emitter) => {
                     let message = SchedulerTick {
                         tick: index + 1,
                         steps,
                     };
-                    MessageType::SchedulerStep
+                    emitter
                         .emit(
-                            tx,
-                            Default::default(),
+                            MessageType::SchedulerStep,
                             Some(&serde_json::json!(message).to_string()),
                         )
                         .map_err(|err| format!("{err}"))
@@ -145,7 +152,7 @@ pub trait Scheduler: Send {
             None => (),
         };
 
-        latents
+        Some(latents)
     }
 
     fn step(&mut self, model_output: &Tensor, timestep: f64, sample: &Tensor) -> Tensor;
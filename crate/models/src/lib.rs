@@ -8,6 +8,7 @@ use crate::diffusion::{
     utils::{linspace, slerp, Bar},
 };
 
+pub mod device;
 pub mod diffusion;
 pub mod llama;
 pub mod sentiment;
@@ -78,7 +79,7 @@ impl Oneshot {
         let mut pipe = Pipe::new(config).expect("cannot create pipe");
 
         println!("- Run pipe\n");
-        pipe.diffuse(&init, &text, true, None)
+        pipe.diffuse(&init, &text, true, None, None)
             .expect("diffusion failed");
 
         drop(no_grad_guard);
@@ -145,7 +146,7 @@ impl Sequence {
         for (k, x) in linspace(0, 1, args.inference) {
             progress_bar.set_position(k);
             let latents = slerp(x, &init1, &init2).expect("slerp failed");
-            pipe.diffuse(&latents, &text, true, None)
+            pipe.diffuse(&latents, &text, true, None, None)
                 .expect("diffusion failed");
         }
         progress_bar.finish_with_message("generation");
@@ -246,7 +247,7 @@ impl Parallel {
                             (interval_size as u64) * (gpu as u64) + k + 1
                         ));
                         slerp(x, &init1, &init2)
-                            .and_then(|latents| pipe.diffuse(&latents, &text, false, None))
+                            .and_then(|latents| pipe.diffuse(&latents, &text, false, None, None))
                             .ok()
                             .unwrap();
                         progress_bar.inc(1);
@@ -1,8 +1,4 @@
-use shared::{
-    message::{emit::Emit, Message},
-    types::MessageType,
-};
-use tokio::sync::broadcast;
+use shared::{control::Checkpoint, message::emit::Emitter, types::MessageType};
 
 use crate::llama::{
     model::{precompute_freqs_cis, CONTEXT_SIZE},
@@ -23,10 +19,15 @@ pub struct Llama {
 
 impl Default for Llama {
     fn default() -> Self {
+        Self::new(tch::Device::cuda_if_available())
+    }
+}
+
+impl Llama {
+    pub fn new(device: tch::Device) -> Self {
         let tokenizer = Tokenizer::from_file(".ai-data/llama_7B/llama-tokenizer.json")
             .expect("Cannot build the Tokenizer");
 
-        let device = tch::Device::cuda_if_available();
         let mut vs = tch::nn::VarStore::new(device);
         let config = Config::config_7b();
 
@@ -69,13 +70,18 @@ pub struct LlamaTick {
 }
 
 impl Llama {
+    pub fn device(&self) -> tch::Device {
+        self.device
+    }
+
     pub fn prediction(
         &self,
         prompt: &str,
         sample_len: usize,
         temperature: f64,
-        tx: Option<broadcast::Sender<Message>>,
-    ) -> String {
+        emitter: Option<Emitter>,
+        checkpoint: Option<&Checkpoint>,
+    ) -> Option<String> {
         let _no_grad = tch::no_grad_guard();
 
         let mut tokens = self
@@ -86,6 +92,12 @@ impl Llama {
         let freqs_cis = precompute_freqs_cis(&self.config).to_device(self.device);
 
         for index in 0..sample_len {
+            if let Some(checkpoint) = checkpoint {
+                if !checkpoint.block() {
+                    return None;
+                }
+            }
+
             let ctxt: Vec<_> = tokens[tokens.len().saturating_sub(CONTEXT_SIZE)..]
                 .iter()
                 .map(|c| *c as i64)
@@ -99,16 +111,15 @@ impl Llama {
             let next_token = i64::try_from(&sampled_y).expect("Prediction error") as usize;
             tokens.push(next_token);
             new_tokens.push(next_token);
-            match &tx {
-                Some(tx) => {
+            match &emitter {
+                Some(emitter) => {
                     let message = LlamaTick {
                         tick: index + 1,
                         steps: sample_len,
                     };
-                    MessageType::LlamaTokenGen
+                    emitter
                         .emit(
-                            tx,
-                            Default::default(),
+                            MessageType::LlamaTokenGen,
                             Some(&serde_json::json!(message).to_string()),
                         )
                         .map_err(|err| format!("{err}"))
@@ -122,7 +133,7 @@ impl Llama {
                 ),
             };
         }
-        self.tokenizer.decode(&new_tokens)
+        Some(self.tokenizer.decode(&new_tokens))
     }
 
     pub fn try_prediction(
@@ -130,7 +141,7 @@ impl Llama {
         prompt: &str,
         sample_len: usize,
         temperature: f64,
-        tx: Option<broadcast::Sender<Message>>,
+        emitter: Option<Emitter>,
     ) -> Result<(), &'static str> {
         let _no_grad = tch::no_grad_guard();
 
@@ -155,16 +166,15 @@ impl Llama {
             let next_token = i64::try_from(&sampled_y).expect("Prediction error") as usize;
             tokens.push(next_token);
             new_tokens.push(next_token);
-            match &tx {
-                Some(tx) => {
+            match &emitter {
+                Some(emitter) => {
                     let message = LlamaTick {
                         tick: index + 1,
                         steps: sample_len,
                     };
-                    MessageType::LlamaTokenGen
+                    emitter
                         .emit(
-                            tx,
-                            Default::default(),
+                            MessageType::LlamaTokenGen,
                             Some(&serde_json::json!(message).to_string()),
                         )
                         .map_err(|err| format!("{err}"))
@@ -6,6 +6,7 @@ use rust_bert::{
     resources::LocalResource,
 };
 use shared::config::path;
+use shared::schema::SentimentOutput;
 
 pub fn get_local_ressources() -> (
     LocalResource,
@@ -29,37 +30,59 @@ pub fn get_local_ressources() -> (
 
 pub struct Sentiment {
     model: SentimentModel,
+    device: tch::Device,
 }
 
 impl Default for Sentiment {
     fn default() -> Self {
+        Self::new(tch::Device::cuda_if_available())
+    }
+}
+
+impl Sentiment {
+    pub fn new(device: tch::Device) -> Self {
         let ressources = get_local_ressources();
-        let config = SequenceClassificationConfig::new(
-            BertModelType::DistilBert,
-            ressources.2,
-            ressources.0,
-            ressources.1,
-            None,
-            true,
-            None,
-            None,
-        );
+        let config = SequenceClassificationConfig {
+            device,
+            ..SequenceClassificationConfig::new(
+                BertModelType::DistilBert,
+                ressources.2,
+                ressources.0,
+                ressources.1,
+                None,
+                true,
+                None,
+                None,
+            )
+        };
 
         Self {
             model: SentimentModel::new(config)
                 .map_err(|error| format!("{error}"))
                 .ok()
                 .unwrap(),
+            device,
         }
     }
-}
 
-impl Sentiment {
-    pub fn prediction(&self, input: &str) -> String {
-        let input: [&str; 1] = [input];
-        let prediction = self.model.predict(&input[..]);
-        let message = format!("{prediction:?}");
-        message
+    pub fn device(&self) -> tch::Device {
+        self.device
+    }
+
+    pub fn prediction(&self, input: &str) -> SentimentOutput {
+        self.predictions(&[input]).remove(0)
+    }
+
+    /// Classifies all the inputs in one forward pass, in order.
+    pub fn predictions(&self, inputs: &[&str]) -> Vec<SentimentOutput> {
+        self.model
+            .predict(inputs)
+            .into_iter()
+            .map(|sentiment| SentimentOutput {
+                label: format!("{:?}", sentiment.polarity),
+                score: sentiment.score,
+            })
+            .collect()
     }
 
     pub fn try_prediction(&self, input: &str) -> Result<(), &'static str> {
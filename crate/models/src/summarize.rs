@@ -31,10 +31,17 @@ pub fn get_local_ressources() -> (
 
 pub struct Summarize {
     model: SummarizationModel,
+    device: tch::Device,
 }
 
 impl Default for Summarize {
     fn default() -> Self {
+        Self::new(tch::Device::cuda_if_available())
+    }
+}
+
+impl Summarize {
+    pub fn new(device: tch::Device) -> Self {
         let ressources = get_local_ressources();
         let merges = Box::new(ressources.3.unwrap());
         let config = SummarizationConfig {
@@ -44,6 +51,7 @@ impl Default for Summarize {
             model_resource: Box::new(ressources.2),
             min_length: 60,
             max_length: None,
+            device,
             ..Default::default()
         };
 
@@ -52,15 +60,21 @@ impl Default for Summarize {
                 .map_err(|error| format!("{error}"))
                 .ok()
                 .unwrap(),
+            device,
         }
     }
-}
 
-impl Summarize {
+    pub fn device(&self) -> tch::Device {
+        self.device
+    }
+
     pub fn prediction(&self, input: &str) -> String {
-        let input: [&str; 1] = [input];
-        let prediction = self.model.summarize(&input[..]);
-        prediction.into_iter().collect::<String>()
+        self.predictions(&[input]).remove(0)
+    }
+
+    /// Summarizes all the inputs in one forward pass, in order.
+    pub fn predictions(&self, inputs: &[&str]) -> Vec<String> {
+        self.model.summarize(inputs)
     }
 
     pub fn try_prediction(&self, input: &str) -> Result<(), &'static str> {
@@ -37,10 +37,17 @@ pub fn get_local_ressources() -> (
 
 pub struct Translation {
     model: TranslationModel,
+    device: Device,
 }
 
 impl Default for Translation {
     fn default() -> Self {
+        Self::new(Device::cuda_if_available())
+    }
+}
+
+impl Translation {
+    pub fn new(device: Device) -> Self {
         let source_languages = M2M100SourceLanguages::M2M100_418M;
         let target_languages = M2M100TargetLanguages::M2M100_418M;
         let ressources = get_local_ressources();
@@ -54,7 +61,7 @@ impl Default for Translation {
             Some(merges),
             source_languages,
             target_languages,
-            Device::cuda_if_available(),
+            device,
         );
 
         Self {
@@ -62,6 +69,7 @@ impl Default for Translation {
                 .map_err(|error| format!("{error}"))
                 .ok()
                 .unwrap(),
+            device,
         }
     }
 }
@@ -78,20 +86,28 @@ fn str_to_lang(value: &str) -> Language {
 }
 
 impl Translation {
+    pub fn device(&self) -> Device {
+        self.device
+    }
+
     pub fn prediction(&self, input: &str, source_lang: &str, target_lang: &str) -> String {
-        let input: [&str; 1] = [input];
-        let prediction = self
-            .model
-            .translate(
-                &input[..],
-                str_to_lang(source_lang),
-                str_to_lang(target_lang),
-            )
+        self.predictions(&[input], source_lang, target_lang)
+            .remove(0)
+    }
+
+    /// Translates all the inputs in one forward pass, in order; they must
+    /// share the same language pair.
+    pub fn predictions(
+        &self,
+        inputs: &[&str],
+        source_lang: &str,
+        target_lang: &str,
+    ) -> Vec<String> {
+        self.model
+            .translate(inputs, str_to_lang(source_lang), str_to_lang(target_lang))
             .map_err(|error| format!("{error}"))
             .ok()
-            .unwrap();
-
-        prediction.into_iter().collect::<String>()
+            .unwrap()
     }
 
     pub fn try_prediction(
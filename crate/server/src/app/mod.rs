@@ -1,17 +1,21 @@
 pub mod private;
 pub mod public;
+pub mod token;
 
-use shared::{command::Command, message::Message};
-use tokio::sync::broadcast;
+use shared::{command::Command, config::Application, message::Message};
+use std::sync::Arc;
+use tokio::sync::{broadcast, watch};
 
 pub async fn router(
     http_tx: tokio::sync::mpsc::Sender<Command>,
     tx: broadcast::Sender<Message>,
+    pool: Arc<sqlx::Pool<sqlx::Postgres>>,
+    config: crate::db::Config,
+    closing: watch::Receiver<bool>,
+    application: watch::Receiver<Application>,
 ) -> axum::Router {
-    let (pool, config) = crate::db::init().await;
-
-    public::route::build(pool.clone(), config.clone())
+    public::route::build(pool.clone(), config.clone(), application.clone())
         .await
-        .merge(private::route::build(http_tx, tx, pool, config).await)
-        .layer(crate::cors::load())
+        .merge(private::route::build(http_tx, tx, pool, config, closing, application.clone()).await)
+        .layer(crate::cors::load(application))
 }
@@ -0,0 +1,223 @@
+use axum::{extract, http::StatusCode, Extension, Json};
+use shared::{
+    constants::page,
+    types::{Role, Scope},
+};
+
+use super::{route::SharedState, ErrorResponse};
+use crate::{app::token, db::model::User};
+
+type Failure = (StatusCode, Json<ErrorResponse>);
+
+fn fail(status: StatusCode, message: &str) -> Failure {
+    let json_error = ErrorResponse {
+        status: "fail",
+        message: message.to_string(),
+    };
+    (status, Json(json_error))
+}
+
+fn database_error(err: sqlx::Error) -> Failure {
+    fail(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        &format!("Error updating users in database: {}", err),
+    )
+}
+
+/// Admin endpoints are reached from a session, never with an API key.
+fn admin(user: &User, scope: &Scope) -> Result<(), Failure> {
+    if user.role() == Role::Admin && !scope.is_restricted() {
+        Ok(())
+    } else {
+        Err(fail(StatusCode::FORBIDDEN, "Admin role required"))
+    }
+}
+
+/// Admins keep their own role and account, so that at least one stays.
+fn not_self(user: &User, id: uuid::Uuid) -> Result<(), Failure> {
+    if user.id == id {
+        Err(fail(
+            StatusCode::CONFLICT,
+            "Admins cannot demote or disable themselves",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn found(user: Option<User>) -> Result<Json<serde_json::Value>, Failure> {
+    let user = user.ok_or_else(|| fail(StatusCode::NOT_FOUND, "No user with this id"))?;
+    Ok(Json(serde_json::json!({
+        "status": "success",
+        "data": serde_json::json!({ "user": user })
+    })))
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct Filter {
+    pub search: Option<String>,
+    pub role: Option<Role>,
+    pub page: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+/// Users whose pubkey contains `search`, most recent first.
+pub async fn list(
+    Extension(user): Extension<User>,
+    Extension(scope): Extension<Scope>,
+    extract::State(state): extract::State<SharedState>,
+    extract::Query(filter): extract::Query<Filter>,
+) -> Result<Json<serde_json::Value>, Failure> {
+    admin(&user, &scope)?;
+    let pool = state.read().await.pool.clone();
+    let limit = filter
+        .limit
+        .unwrap_or(page::LIMIT)
+        .clamp(1, page::MAX_LIMIT);
+    let page = filter.page.unwrap_or(1).max(1);
+
+    let users = sqlx::query_as!(
+        User,
+        r#"SELECT * FROM users
+        WHERE ($1::VARCHAR IS NULL OR pubkey ILIKE '%' || $1 || '%')
+            AND ($2::VARCHAR IS NULL OR role = $2)
+        ORDER BY created_at DESC
+        LIMIT $3 OFFSET $4"#,
+        filter.search,
+        filter.role.map(|role| role.to_string()),
+        limit,
+        (page - 1) * limit,
+    )
+    .fetch_all(pool.as_ref())
+    .await
+    .map_err(database_error)?;
+
+    Ok(Json(serde_json::json!({
+        "status": "success",
+        "data": serde_json::json!({
+            "users": users,
+            "page": page,
+            "limit": limit,
+        })
+    })))
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct RoleUpdate {
+    pub role: Role,
+}
+
+pub async fn role(
+    Extension(user): Extension<User>,
+    Extension(scope): Extension<Scope>,
+    extract::State(state): extract::State<SharedState>,
+    extract::Path(id): extract::Path<uuid::Uuid>,
+    Json(body): Json<RoleUpdate>,
+) -> Result<Json<serde_json::Value>, Failure> {
+    admin(&user, &scope)?;
+    not_self(&user, id)?;
+    let pool = state.read().await.pool.clone();
+
+    let user = sqlx::query_as!(
+        User,
+        "UPDATE users SET role = $2, updated_at = NOW() WHERE id = $1 RETURNING *",
+        id,
+        body.role.to_string(),
+    )
+    .fetch_optional(pool.as_ref())
+    .await
+    .map_err(database_error)?;
+
+    found(user)
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct VerifiedUpdate {
+    pub verified: bool,
+}
+
+pub async fn verified(
+    Extension(user): Extension<User>,
+    Extension(scope): Extension<Scope>,
+    extract::State(state): extract::State<SharedState>,
+    extract::Path(id): extract::Path<uuid::Uuid>,
+    Json(body): Json<VerifiedUpdate>,
+) -> Result<Json<serde_json::Value>, Failure> {
+    admin(&user, &scope)?;
+    let pool = state.read().await.pool.clone();
+
+    let user = sqlx::query_as!(
+        User,
+        "UPDATE users SET verified = $2, updated_at = NOW() WHERE id = $1 RETURNING *",
+        id,
+        body.verified,
+    )
+    .fetch_optional(pool.as_ref())
+    .await
+    .map_err(database_error)?;
+
+    found(user)
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct DisabledUpdate {
+    pub disabled: bool,
+}
+
+/// Disabling an account also revokes its tokens, so that they stay refused
+/// once it is enabled again.
+pub async fn disabled(
+    Extension(user): Extension<User>,
+    Extension(scope): Extension<Scope>,
+    extract::State(state): extract::State<SharedState>,
+    extract::Path(id): extract::Path<uuid::Uuid>,
+    Json(body): Json<DisabledUpdate>,
+) -> Result<Json<serde_json::Value>, Failure> {
+    admin(&user, &scope)?;
+    not_self(&user, id)?;
+    let pool = state.read().await.pool.clone();
+
+    let user = sqlx::query_as!(
+        User,
+        r#"UPDATE users SET disabled = $2, updated_at = NOW(),
+            tokens_revoked_at = CASE WHEN $2 THEN NOW() ELSE tokens_revoked_at END
+        WHERE id = $1 RETURNING *"#,
+        id,
+        body.disabled,
+    )
+    .fetch_optional(pool.as_ref())
+    .await
+    .map_err(database_error)?;
+
+    if body.disabled {
+        token::revoke_all(pool.as_ref(), id)
+            .await
+            .map_err(database_error)?;
+    }
+    found(user)
+}
+
+/// Refuses every token and API key issued to the user so far.
+pub async fn revoke(
+    Extension(user): Extension<User>,
+    Extension(scope): Extension<Scope>,
+    extract::State(state): extract::State<SharedState>,
+    extract::Path(id): extract::Path<uuid::Uuid>,
+) -> Result<Json<serde_json::Value>, Failure> {
+    admin(&user, &scope)?;
+    let pool = state.read().await.pool.clone();
+
+    let user = sqlx::query_as!(
+        User,
+        "UPDATE users SET tokens_revoked_at = NOW(), updated_at = NOW() WHERE id = $1 RETURNING *",
+        id,
+    )
+    .fetch_optional(pool.as_ref())
+    .await
+    .map_err(database_error)?;
+
+    token::revoke_all(pool.as_ref(), id)
+        .await
+        .map_err(database_error)?;
+    found(user)
+}
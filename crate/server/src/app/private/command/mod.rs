@@ -1,41 +1,342 @@
 pub mod playload;
 
 use axum::{extract, http::StatusCode, Extension, Json};
-use shared::{command::instruction, command::Command, types::CommandType};
+use shared::{
+    command::instruction,
+    command::Command,
+    config,
+    constants::time,
+    message::{
+        emit::{Emit, EmitSource},
+        Message, Owner,
+    },
+    schema::{ModelInput, SchemaError},
+    types::{CommandType, MessageType, ModelType, Role, Scope},
+};
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
 
 use playload::Playload;
 
 use crate::db::model::User;
 
-use super::route::SharedState;
+use super::{
+    jobs,
+    route::{SharedState, State},
+};
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Response<T> {
+    #[serde(flatten)]
+    pub payload: T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
 
 pub async fn handler<T>(
     Extension(user): Extension<User>,
+    Extension(scope): Extension<Scope>,
     extract::State(state): extract::State<SharedState>,
     Json(payload): Json<T>,
-) -> (StatusCode, Json<T>)
+) -> (StatusCode, Json<Response<T>>)
 where
     T: Playload + std::fmt::Debug,
 {
-    let command = command_from(&user.pubkey, &payload);
-    state.read().await.http_tx.send(command).await.unwrap();
-    (StatusCode::CREATED, Json(payload))
+    let state = state.read().await;
+    if *state.closing.borrow() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(Response {
+                payload,
+                job_id: None,
+                error: None,
+            }),
+        );
+    }
+
+    let rx = state.tx.subscribe();
+    let source = match submit(&state, &user, &scope, &payload).await {
+        Ok(source) => source,
+        Err(error) => {
+            return (
+                error.status(),
+                Json(Response {
+                    payload,
+                    job_id: None,
+                    error: Some(error.to_string()),
+                }),
+            )
+        }
+    };
+
+    let verdict = verdict(rx, &source).await;
+    let (job_id, error) = match verdict {
+        Verdict::Done | Verdict::Unknown => (source.job_id(), None),
+        Verdict::Full => (None, None),
+        Verdict::Forbidden(error) | Verdict::Failed(error) => (None, Some(error)),
+    };
+    (
+        verdict.status(),
+        Json(Response {
+            payload,
+            job_id,
+            error,
+        }),
+    )
+}
+
+/// What the supervisor made of a command.
+#[derive(Debug)]
+enum Verdict {
+    /// Queued, or carried out.
+    Done,
+    Full,
+    Forbidden(String),
+    Failed(String),
+    /// No answer in time, the command may still be carried out.
+    Unknown,
+}
+
+impl Verdict {
+    fn status(&self) -> StatusCode {
+        match self {
+            Verdict::Done => StatusCode::CREATED,
+            Verdict::Full => StatusCode::TOO_MANY_REQUESTS,
+            Verdict::Forbidden(_) => StatusCode::FORBIDDEN,
+            Verdict::Failed(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Verdict::Unknown => StatusCode::ACCEPTED,
+        }
+    }
+}
+
+/// Whether a `CommandFailed` error is a role check the supervisor turned
+/// down; it sends the `Debug` name of its error.
+pub(crate) fn forbidden(error: &str) -> bool {
+    error.contains("Forbidden")
+}
+
+/// Why a command was not sent to the supervisor.
+#[derive(Debug)]
+pub enum Refusal {
+    Invalid(SchemaError),
+    /// The `command_type` of the body is not the one of its endpoint.
+    Mismatch {
+        declared: CommandType,
+        expected: CommandType,
+    },
+    Forbidden(String),
+    /// The supervisor stopped taking commands, the server is shutting down.
+    Unavailable,
+}
+
+impl std::fmt::Display for Refusal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Refusal::Invalid(error) => write!(f, "{error}"),
+            Refusal::Mismatch { declared, expected } => {
+                write!(f, "`command_type` is {declared:?}, expected {expected:?}")
+            }
+            Refusal::Forbidden(reason) => write!(f, "{reason}"),
+            Refusal::Unavailable => write!(f, "Supervisor stopped"),
+        }
+    }
 }
 
-fn command_from<T: Playload + std::fmt::Debug>(user_id: &str, payload: &T) -> Command {
+impl Refusal {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Refusal::Invalid(_) | Refusal::Mismatch { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            Refusal::Forbidden(_) => StatusCode::FORBIDDEN,
+            Refusal::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+}
+
+/// Checks the role of the user against the owner of the targeted worker, as
+/// last reported by the supervisor. A denied command is reported with
+/// `CommandFailed`. Workers not reported yet are left to the supervisor,
+/// which checks every command again; its refusal is answered with 403 too.
+async fn authorize(state: &State, user: &User, command: &Command) -> Result<(), Refusal> {
+    let instruction = command.boxed_instruction();
+    let Some(task_id) = instruction.task_id() else {
+        return Ok(());
+    };
+    let worker = state
+        .health
+        .read()
+        .await
+        .workers
+        .iter()
+        .find(|worker| worker.task_id == task_id)
+        .map(|worker| (worker.owner.clone(), worker.model_type));
+
+    let role = user.role();
+    let command_type = instruction.command_type();
+    match worker {
+        Some((owner, model_type)) if !role.may(command_type, model_type, &user.pubkey, &owner) => {
+            let reason =
+                format!("Forbidden; {command_type:?} not allowed on this worker for {role:?}");
+            if let Err(err) =
+                MessageType::CommandFailed.emit(&state.tx, instruction.into(), Some(&reason))
+            {
+                println!("{err:#?}");
+            }
+            Err(Refusal::Forbidden(reason))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The model type of the worker or the job a command acts on, as last
+/// reported, or the one of the payload when the target is not known yet. The
+/// supervisor refuses commands whose model type differs from their target.
+async fn target_model_type<T: Playload>(state: &State, payload: &T) -> ModelType {
+    let target = match (payload.command_type(), payload.task_id(), payload.job_id()) {
+        (CommandType::Cancel, _, Some(job_id)) => jobs::model_type(&state.pool, &job_id).await,
+        (_, Some(task_id), _) => state
+            .health
+            .read()
+            .await
+            .workers
+            .iter()
+            .find(|worker| worker.task_id == task_id)
+            .map(|worker| worker.model_type),
+        _ => None,
+    };
+    target.unwrap_or(payload.model_type())
+}
+
+/// Checks the payload, the scope of the request and the role of the user,
+/// then sends the command to the supervisor, with what identifies its
+/// verdict. Process commands are recorded in the job history first.
+pub(crate) async fn submit<T: Playload + std::fmt::Debug>(
+    state: &State,
+    user: &User,
+    scope: &Scope,
+    payload: &T,
+) -> Result<EmitSource, Refusal> {
+    let command_type = payload.command_type();
+    if payload.declared_command_type() != command_type {
+        return Err(Refusal::Mismatch {
+            declared: payload.declared_command_type(),
+            expected: command_type,
+        });
+    }
+    for model_type in [
+        payload.model_type(),
+        target_model_type(state, payload).await,
+    ] {
+        if !scope.allows(command_type, model_type) {
+            return Err(Refusal::Forbidden(format!(
+                "Forbidden; {command_type:?} on {model_type:?} not allowed for this API key"
+            )));
+        }
+    }
+    let (diffusion, limits) = {
+        let application = state.application.borrow();
+        (application.diffusion, application.limits)
+    };
+    let command = command_from(&user.pubkey, user.role(), payload, &diffusion, &limits)
+        .map_err(Refusal::Invalid)?;
+    authorize(state, user, &command).await?;
+
+    if let Command::Process(instruction) = &command {
+        if let Err(err) = jobs::submitted(&state.pool, user.id, instruction).await {
+            println!("{err:#?}");
+        }
+    }
+
+    let source = EmitSource::from(command.boxed_instruction());
+    if state.http_tx.send(command).await.is_err() {
+        if let Some(job_id) = source.job_id() {
+            jobs::abandoned(&state.pool, &job_id, "Supervisor stopped").await;
+        }
+        return Err(Refusal::Unavailable);
+    }
+    Ok(source)
+}
+
+/// Whether the message is the supervisor's answer to the command of
+/// `source`. Commands without a job are told apart by owner, type, model and
+/// targeted worker.
+fn answers(message: &Message, source: &EmitSource) -> bool {
+    if let Some(job_id) = source.job_id() {
+        return message.job_id() == Some(job_id);
+    }
+    let (command_type, task_id) = match message {
+        Message::CommandSucess(data) => (data.command_type, Some(data.task_id.clone())),
+        Message::CommandFailed(data) => (data.command_type, data.task_id.clone()),
+        _ => return false,
+    };
+    message.job_id().is_none()
+        && source.command_type() == Some(command_type)
+        && source.owner() == message.owner()
+        && source.model_type() == message.model_type()
+        && source
+            .task_id()
+            .iter()
+            .all(|target| task_id.as_ref() == Some(target))
+}
+
+/// Waits for the supervisor to queue, carry out or reject the command, for
+/// at most one health interval.
+async fn verdict(mut rx: broadcast::Receiver<Message>, source: &EmitSource) -> Verdict {
+    let verdict = async {
+        loop {
+            match rx.recv().await {
+                Ok(message) if answers(&message, source) => match message {
+                    Message::QueueFull(_) => return Verdict::Full,
+                    Message::JobQueued(_) | Message::CommandSucess(_) => return Verdict::Done,
+                    Message::CommandFailed(data) if forbidden(&data.error) => {
+                        return Verdict::Forbidden(data.error)
+                    }
+                    Message::CommandFailed(data) => return Verdict::Failed(data.error),
+                    _ => continue,
+                },
+                Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return Verdict::Unknown,
+            }
+        }
+    };
+    tokio::time::timeout(Duration::from_millis(time::INTERVAL), verdict)
+        .await
+        .unwrap_or(Verdict::Unknown)
+}
+
+/// Builds the command sent to the supervisor; process inputs are checked
+/// against the model they target, and the configured `limits`, before
+/// anything is queued. Diffusion inputs get the configured defaults for the
+/// fields they leave out.
+fn command_from<T: Playload + std::fmt::Debug>(
+    user_id: &str,
+    role: Role,
+    payload: &T,
+    diffusion: &config::Diffusion,
+    limits: &config::Limits,
+) -> Result<Command, SchemaError> {
+    let mut input = payload.input().unwrap_or_default();
+    if payload.model_type() == ModelType::Diffusion {
+        diffusion.fill(&mut input);
+    }
+
     let tag = payload.command_type();
-    match tag {
+    let command = match tag {
         CommandType::Process => Command::Process(instruction::Process {
             timestamp: shared::tools::time(),
             owner: user_id.to_string(),
+            role,
             command_type: CommandType::Process,
             model_type: payload.model_type(),
             task_id: payload.task_id(),
-            json_input: payload.json_input().unwrap(),
+            job_id: shared::tools::job_id(),
+            input: ModelInput::parse(payload.model_type(), input, limits)?,
         }),
         CommandType::Kill => Command::Kill(instruction::Kill {
             timestamp: shared::tools::time(),
             owner: user_id.to_string(),
+            role,
             command_type: CommandType::Kill,
             model_type: payload.model_type(),
             task_id: payload.task_id().unwrap(),
@@ -43,6 +344,7 @@ fn command_from<T: Playload + std::fmt::Debug>(user_id: &str, payload: &T) -> Co
         CommandType::Pause => Command::Pause(instruction::Pause {
             timestamp: shared::tools::time(),
             owner: user_id.to_string(),
+            role,
             command_type: CommandType::Pause,
             model_type: payload.model_type(),
             task_id: payload.task_id().unwrap(),
@@ -50,6 +352,7 @@ fn command_from<T: Playload + std::fmt::Debug>(user_id: &str, payload: &T) -> Co
         CommandType::Resume => Command::Resume(instruction::Resume {
             timestamp: shared::tools::time(),
             owner: user_id.to_string(),
+            role,
             command_type: CommandType::Resume,
             model_type: payload.model_type(),
             task_id: payload.task_id().unwrap(),
@@ -57,8 +360,19 @@ fn command_from<T: Playload + std::fmt::Debug>(user_id: &str, payload: &T) -> Co
         CommandType::Spawn => Command::Spawn(instruction::Spawn {
             timestamp: shared::tools::time(),
             owner: user_id.to_string(),
+            role,
             command_type: CommandType::Spawn,
             model_type: payload.model_type(),
+            device: payload.device().unwrap_or_default(),
         }),
-    }
+        CommandType::Cancel => Command::Cancel(instruction::Cancel {
+            timestamp: shared::tools::time(),
+            owner: user_id.to_string(),
+            role,
+            command_type: CommandType::Cancel,
+            model_type: payload.model_type(),
+            job_id: payload.job_id().unwrap(),
+        }),
+    };
+    Ok(command)
 }
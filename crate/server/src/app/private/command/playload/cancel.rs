@@ -0,0 +1,48 @@
+use shared::types::{CommandType, Device, ModelType};
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Cancel {
+    pub command_type: CommandType,
+    pub model_type: ModelType,
+    pub job_id: String,
+}
+
+impl Cancel {
+    pub fn new(model_type: ModelType, job_id: &str) -> Self {
+        Self {
+            command_type: CommandType::Cancel,
+            model_type,
+            job_id: job_id.to_string(),
+        }
+    }
+}
+
+impl super::Playload for Cancel {
+    fn command_type(&self) -> CommandType {
+        CommandType::Cancel
+    }
+
+    fn declared_command_type(&self) -> CommandType {
+        self.command_type
+    }
+
+    fn model_type(&self) -> ModelType {
+        self.model_type
+    }
+
+    fn task_id(&self) -> Option<String> {
+        None
+    }
+
+    fn input(&self) -> Option<serde_json::Value> {
+        None
+    }
+
+    fn job_id(&self) -> Option<String> {
+        Some(self.job_id.clone())
+    }
+
+    fn device(&self) -> Option<Device> {
+        None
+    }
+}
@@ -1,4 +1,4 @@
-use shared::types::{CommandType, ModelType};
+use shared::types::{CommandType, Device, ModelType};
 
 #[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct Kill {
@@ -19,6 +19,10 @@ impl Kill {
 
 impl super::Playload for Kill {
     fn command_type(&self) -> CommandType {
+        CommandType::Kill
+    }
+
+    fn declared_command_type(&self) -> CommandType {
         self.command_type
     }
 
@@ -30,7 +34,15 @@ impl super::Playload for Kill {
         Some(self.task_id.clone())
     }
 
-    fn json_input(&self) -> Option<String> {
+    fn input(&self) -> Option<serde_json::Value> {
+        None
+    }
+
+    fn job_id(&self) -> Option<String> {
+        None
+    }
+
+    fn device(&self) -> Option<Device> {
         None
     }
 }
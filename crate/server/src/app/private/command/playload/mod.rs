@@ -1,10 +1,15 @@
-use shared::types::{CommandType, ModelType};
+use shared::types::{CommandType, Device, ModelType};
 
 pub trait Playload: Send + Sync {
+    /// The command of the payload type, whatever the body says.
     fn command_type(&self) -> CommandType;
+    /// The `command_type` field of the body.
+    fn declared_command_type(&self) -> CommandType;
     fn model_type(&self) -> ModelType;
     fn task_id(&self) -> Option<String>;
-    fn json_input(&self) -> Option<String>;
+    fn input(&self) -> Option<serde_json::Value>;
+    fn job_id(&self) -> Option<String>;
+    fn device(&self) -> Option<Device>;
 }
 
 pub mod spawn;
@@ -21,3 +26,6 @@ pub use process::Process;
 
 pub mod kill;
 pub use kill::Kill;
+
+pub mod cancel;
+pub use cancel::Cancel;
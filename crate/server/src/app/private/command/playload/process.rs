@@ -1,19 +1,19 @@
-use shared::types::{CommandType, ModelType};
+use shared::types::{CommandType, Device, ModelType};
 
 #[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct Process {
     pub command_type: CommandType,
     pub model_type: ModelType,
-    pub json_input: String,
+    pub input: serde_json::Value,
     pub task_id: Option<String>,
 }
 
 impl Process {
-    pub fn new(model_type: ModelType, task_id: &str, json_input: &str) -> Self {
+    pub fn new(model_type: ModelType, task_id: &str, input: serde_json::Value) -> Self {
         Self {
             command_type: CommandType::Process,
             model_type,
-            json_input: json_input.to_owned(),
+            input,
             task_id: Some(task_id.to_owned()),
         }
     }
@@ -21,6 +21,10 @@ impl Process {
 
 impl super::Playload for Process {
     fn command_type(&self) -> CommandType {
+        CommandType::Process
+    }
+
+    fn declared_command_type(&self) -> CommandType {
         self.command_type
     }
 
@@ -32,7 +36,15 @@ impl super::Playload for Process {
         self.task_id.to_owned()
     }
 
-    fn json_input(&self) -> Option<String> {
-        Some(self.json_input.to_owned())
+    fn input(&self) -> Option<serde_json::Value> {
+        Some(self.input.clone())
+    }
+
+    fn job_id(&self) -> Option<String> {
+        None
+    }
+
+    fn device(&self) -> Option<Device> {
+        None
     }
 }
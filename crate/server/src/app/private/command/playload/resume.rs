@@ -1,4 +1,4 @@
-use shared::types::{CommandType, ModelType};
+use shared::types::{CommandType, Device, ModelType};
 
 #[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct Resume {
@@ -19,6 +19,10 @@ impl Resume {
 
 impl super::Playload for Resume {
     fn command_type(&self) -> CommandType {
+        CommandType::Resume
+    }
+
+    fn declared_command_type(&self) -> CommandType {
         self.command_type
     }
 
@@ -30,7 +34,15 @@ impl super::Playload for Resume {
         Some(self.task_id.clone())
     }
 
-    fn json_input(&self) -> Option<String> {
+    fn input(&self) -> Option<serde_json::Value> {
+        None
+    }
+
+    fn job_id(&self) -> Option<String> {
+        None
+    }
+
+    fn device(&self) -> Option<Device> {
         None
     }
 }
@@ -1,9 +1,11 @@
-use shared::types::{CommandType, ModelType};
+use shared::types::{CommandType, Device, ModelType};
 
 #[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct Spawn {
     pub command_type: CommandType,
     pub model_type: ModelType,
+    #[serde(default)]
+    pub device: Device,
 }
 
 impl Spawn {
@@ -11,12 +13,17 @@ impl Spawn {
         Self {
             command_type: CommandType::Spawn,
             model_type,
+            device: Device::Auto,
         }
     }
 }
 
 impl super::Playload for Spawn {
     fn command_type(&self) -> CommandType {
+        CommandType::Spawn
+    }
+
+    fn declared_command_type(&self) -> CommandType {
         self.command_type
     }
 
@@ -28,7 +35,15 @@ impl super::Playload for Spawn {
         None
     }
 
-    fn json_input(&self) -> Option<String> {
+    fn input(&self) -> Option<serde_json::Value> {
         None
     }
+
+    fn job_id(&self) -> Option<String> {
+        None
+    }
+
+    fn device(&self) -> Option<Device> {
+        Some(self.device)
+    }
 }
@@ -0,0 +1,183 @@
+use axum::{extract, http::StatusCode, Extension, Json};
+use shared::{
+    command::{instruction, Command},
+    message::Message,
+    schema::{ModelInput, ModelOutput},
+    types::{CommandType, ModelType, Scope},
+};
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use super::{command, jobs, route::SharedState};
+use crate::db::model::User;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Response {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<ModelOutput>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Response {
+    fn error(job_id: Option<String>, error: &str) -> Json<Self> {
+        Json(Response {
+            job_id,
+            output: None,
+            error: Some(error.to_owned()),
+        })
+    }
+}
+
+/// The model behind each `/api/v1` endpoint.
+fn model_type(name: &str) -> Option<ModelType> {
+    match name {
+        "sentiment" => Some(ModelType::Sentiment),
+        "summarize" => Some(ModelType::Summarize),
+        "translate" => Some(ModelType::Translation),
+        "generate" => Some(ModelType::Llama),
+        "diffusion" => Some(ModelType::Diffusion),
+        _ => None,
+    }
+}
+
+/// Follows the job on the bus until it ends, with the status to answer.
+async fn outcome(
+    mut rx: broadcast::Receiver<Message>,
+    job_id: &str,
+) -> (StatusCode, Option<ModelOutput>, Option<String>) {
+    let mut output = None;
+    loop {
+        match rx.recv().await {
+            Ok(message) if message.job_id().as_deref() == Some(job_id) => match message {
+                Message::ModelPrediction(data) => output = Some(data.output),
+                Message::JobDone(_) => return (StatusCode::OK, output, None),
+                Message::JobFailed(data) => {
+                    return (StatusCode::INTERNAL_SERVER_ERROR, None, Some(data.error))
+                }
+                Message::JobCancelled(_) => {
+                    return (StatusCode::CONFLICT, None, Some("Job cancelled".to_owned()))
+                }
+                Message::QueueFull(_) => {
+                    return (
+                        StatusCode::TOO_MANY_REQUESTS,
+                        None,
+                        Some("Queue full".to_owned()),
+                    )
+                }
+                Message::CommandFailed(data) if command::forbidden(&data.error) => {
+                    return (StatusCode::FORBIDDEN, None, Some(data.error))
+                }
+                Message::CommandFailed(data) => {
+                    return (StatusCode::SERVICE_UNAVAILABLE, None, Some(data.error))
+                }
+                _ => continue,
+            },
+            Ok(_) | Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => {
+                return (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    None,
+                    Some("Supervisor stopped".to_owned()),
+                )
+            }
+        }
+    }
+}
+
+/// Runs the input on a worker of the model and answers with its prediction.
+/// A job still running after `server.inference_timeout` is answered with 504
+/// and its id, its result can then be read from `/api/jobs/:id`.
+pub async fn handler(
+    Extension(user): Extension<User>,
+    Extension(scope): Extension<Scope>,
+    extract::State(state): extract::State<SharedState>,
+    extract::Path(model): extract::Path<String>,
+    Json(mut input): Json<serde_json::Value>,
+) -> (StatusCode, Json<Response>) {
+    let Some(model_type) = model_type(&model) else {
+        return (StatusCode::NOT_FOUND, Response::error(None, "Unknown model"));
+    };
+    if !scope.allows(CommandType::Process, model_type) {
+        return (
+            StatusCode::FORBIDDEN,
+            Response::error(None, "Model not allowed for this API key"),
+        );
+    }
+
+    let state = state.read().await;
+    if *state.closing.borrow() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Response::error(None, "Server closing"),
+        );
+    }
+
+    let (diffusion, limits, timeout) = {
+        let application = state.application.borrow();
+        (
+            application.diffusion,
+            application.limits,
+            application.server.inference_timeout,
+        )
+    };
+    if model_type == ModelType::Diffusion {
+        diffusion.fill(&mut input);
+    }
+    let input = match ModelInput::parse(model_type, input, &limits) {
+        Ok(input) => input,
+        Err(error) => {
+            return (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Response::error(None, &error.to_string()),
+            )
+        }
+    };
+
+    let instruction = instruction::Process {
+        timestamp: shared::tools::time(),
+        owner: user.pubkey.clone(),
+        role: user.role(),
+        command_type: CommandType::Process,
+        model_type,
+        task_id: None,
+        job_id: shared::tools::job_id(),
+        input,
+    };
+    if let Err(err) = jobs::submitted(&state.pool, user.id, &instruction).await {
+        println!("{err:#?}");
+    }
+
+    let job_id = instruction.job_id.clone();
+    let rx = state.tx.subscribe();
+    if state
+        .http_tx
+        .send(Command::Process(instruction))
+        .await
+        .is_err()
+    {
+        jobs::abandoned(&state.pool, &job_id, "Supervisor stopped").await;
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Response::error(None, "Supervisor stopped"),
+        );
+    }
+    drop(state);
+
+    match tokio::time::timeout(Duration::from_millis(timeout), outcome(rx, &job_id)).await {
+        Ok((status, output, error)) => (
+            status,
+            Json(Response {
+                job_id: Some(job_id),
+                output,
+                error,
+            }),
+        ),
+        Err(_) => (
+            StatusCode::GATEWAY_TIMEOUT,
+            Response::error(Some(job_id), "Prediction not ready in time"),
+        ),
+    }
+}
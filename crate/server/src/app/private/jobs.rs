@@ -0,0 +1,382 @@
+use axum::{extract, http::StatusCode, Extension, Json};
+use chrono::{DateTime, Utc};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{
+    broadcast::{self, error::RecvError},
+    mpsc,
+};
+
+use shared::{
+    command::instruction,
+    constants::{chan, page},
+    message::Message,
+    types::{CommandType, JobStatus, ModelType},
+};
+
+use super::{route::SharedState, ErrorResponse};
+use crate::db::model::{Job, User};
+
+type Pool = Arc<sqlx::Pool<sqlx::Postgres>>;
+
+/// Records a process command before it is sent to the supervisor, so that
+/// the messages following it always find its row.
+pub async fn submitted(
+    pool: &sqlx::Pool<sqlx::Postgres>,
+    user_id: uuid::Uuid,
+    instruction: &instruction::Process,
+) -> Result<(), sqlx::Error> {
+    let id = uuid::Uuid::parse_str(&instruction.job_id).unwrap_or_default();
+    let input = serde_json::to_value(&instruction.input).unwrap_or_default();
+    sqlx::query!(
+        "INSERT INTO jobs (id, user_id, model_type, input) VALUES ($1, $2, $3, $4)",
+        id,
+        user_id,
+        format!("{:?}", instruction.model_type),
+        input,
+    )
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
+
+/// Fails a recorded job that never reached the supervisor.
+pub async fn abandoned(pool: &sqlx::Pool<sqlx::Postgres>, job_id: &str, error: &str) {
+    let Ok(id) = uuid::Uuid::parse_str(job_id) else {
+        return;
+    };
+    let result = sqlx::query!(
+        "UPDATE jobs SET status = $2, error = $3, finished_at = NOW(), duration_ms = 0 WHERE id = $1",
+        id,
+        format!("{:?}", JobStatus::Failed),
+        error,
+    )
+    .execute(pool)
+    .await;
+    if let Err(err) = result {
+        println!("{err:#?}");
+    }
+}
+
+/// Model type of a recorded job.
+pub async fn model_type(pool: &sqlx::Pool<sqlx::Postgres>, job_id: &str) -> Option<ModelType> {
+    let id = uuid::Uuid::parse_str(job_id).ok()?;
+    sqlx::query_scalar!("SELECT model_type FROM jobs WHERE id = $1", id)
+        .fetch_optional(pool)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|model_type| model_type.parse().ok())
+}
+
+/// A write of the recorder to the `jobs` table.
+#[derive(Debug)]
+enum Update {
+    Start(uuid::Uuid),
+    Output(uuid::Uuid, serde_json::Value),
+    Finish(uuid::Uuid, JobStatus, Option<String>),
+    /// Fails the jobs in flight since before the recorder lagged, or a write
+    /// failed, that the supervisor no longer holds; their last messages were
+    /// lost.
+    Reconcile {
+        before: DateTime<Utc>,
+        live: Vec<uuid::Uuid>,
+    },
+}
+
+impl Update {
+    fn of(message: &Message) -> Option<Self> {
+        let id = message
+            .job_id()
+            .and_then(|job_id| uuid::Uuid::parse_str(&job_id).ok())?;
+        let finish = |status, error: &str| Update::Finish(id, status, Some(error.to_owned()));
+
+        match message {
+            Message::JobRunning(_) => Some(Update::Start(id)),
+            Message::ModelPrediction(data) => {
+                Some(Update::Output(id, serde_json::json!(data.output)))
+            }
+            Message::JobDone(_) => Some(Update::Finish(id, JobStatus::Done, None)),
+            Message::JobFailed(data) => Some(finish(JobStatus::Failed, &data.error)),
+            Message::JobCancelled(_) => Some(Update::Finish(id, JobStatus::Cancelled, None)),
+            Message::QueueFull(_) => Some(finish(JobStatus::Failed, "Queue full")),
+            Message::CommandFailed(data) if data.command_type == CommandType::Process => {
+                Some(finish(JobStatus::Failed, &data.error))
+            }
+            _ => None,
+        }
+    }
+}
+
+async fn start(pool: &Pool, ids: Vec<uuid::Uuid>) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE jobs SET status = $2, started_at = NOW() WHERE id = ANY($1) AND status = $3",
+        &ids,
+        format!("{:?}", JobStatus::Running),
+        format!("{:?}", JobStatus::Queued),
+    )
+    .execute(pool.as_ref())
+    .await
+    .map(|_| ())
+}
+
+async fn output(
+    pool: &Pool,
+    ids: Vec<uuid::Uuid>,
+    outputs: Vec<serde_json::Value>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"UPDATE jobs SET output = o.output
+        FROM UNNEST($1::UUID[], $2::JSONB[]) AS o(id, output)
+        WHERE jobs.id = o.id"#,
+        &ids,
+        &outputs,
+    )
+    .execute(pool.as_ref())
+    .await
+    .map(|_| ())
+}
+
+/// Moves jobs still in flight to a terminal status and stores their
+/// duration, counted from their start or from their submission when they
+/// never ran.
+async fn finish(
+    pool: &Pool,
+    ids: Vec<uuid::Uuid>,
+    statuses: Vec<String>,
+    errors: Vec<Option<String>>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"UPDATE jobs SET status = f.status, error = COALESCE(f.error, jobs.error), finished_at = NOW(),
+            duration_ms = (EXTRACT(EPOCH FROM NOW() - COALESCE(jobs.started_at, jobs.created_at)) * 1000)::BIGINT
+        FROM UNNEST($1::UUID[], $2::VARCHAR[], $3::VARCHAR[]) AS f(id, status, error)
+        WHERE jobs.id = f.id AND jobs.status IN ($4, $5)"#,
+        &ids,
+        &statuses,
+        &errors,
+        format!("{:?}", JobStatus::Queued),
+        format!("{:?}", JobStatus::Running),
+    )
+    .execute(pool.as_ref())
+    .await
+    .map(|_| ())
+}
+
+async fn reconcile(
+    pool: &Pool,
+    before: DateTime<Utc>,
+    live: Vec<uuid::Uuid>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"UPDATE jobs SET status = $1, error = $2, finished_at = NOW(),
+            duration_ms = (EXTRACT(EPOCH FROM NOW() - COALESCE(started_at, created_at)) * 1000)::BIGINT
+        WHERE status IN ($3, $4) AND created_at < $5 AND NOT (id = ANY($6))"#,
+        format!("{:?}", JobStatus::Failed),
+        "Outcome lost, the job history fell behind",
+        format!("{:?}", JobStatus::Queued),
+        format!("{:?}", JobStatus::Running),
+        before,
+        &live,
+    )
+    .execute(pool.as_ref())
+    .await
+    .map(|_| ())
+}
+
+/// Writes a batch in as few statements as it takes. Starts, outputs and
+/// finishes are applied in this order, and the first finish of a job wins
+/// as it would one message at a time.
+async fn write(pool: &Pool, batch: Vec<Update>) -> Result<(), sqlx::Error> {
+    let mut starts = vec![];
+    let (mut outputs, mut values) = (vec![], vec![]);
+    let (mut finished, mut statuses, mut errors) = (vec![], vec![], vec![]);
+    let mut reconciles = vec![];
+    for update in batch {
+        match update {
+            Update::Start(id) => starts.push(id),
+            Update::Output(id, value) => {
+                outputs.push(id);
+                values.push(value);
+            }
+            Update::Finish(id, status, error) if !finished.contains(&id) => {
+                finished.push(id);
+                statuses.push(format!("{status:?}"));
+                errors.push(error);
+            }
+            Update::Finish(..) => (),
+            Update::Reconcile { before, live } => reconciles.push((before, live)),
+        }
+    }
+
+    if !starts.is_empty() {
+        start(pool, starts).await?;
+    }
+    if !outputs.is_empty() {
+        output(pool, outputs, values).await?;
+    }
+    if !finished.is_empty() {
+        finish(pool, finished, statuses, errors).await?;
+    }
+    for (before, live) in reconciles {
+        reconcile(pool, before, live).await?;
+    }
+    Ok(())
+}
+
+/// Follows the job messages on the bus and writes them to the `jobs` table
+/// in batches, off the receiving task. When the recorder falls behind the
+/// bus, or a batch fails to be written, the next health report tells which
+/// jobs the supervisor still holds, and the older ones still in flight are
+/// marked failed.
+pub fn record(tx: &broadcast::Sender<Message>, pool: Pool) {
+    let mut rx = tx.subscribe();
+    let (updates, mut pending) = mpsc::channel::<Update>(chan::MPSC_LEN);
+    // When updates were last lost, until a health report reconciles them.
+    let lost: Arc<Mutex<Option<DateTime<Utc>>>> = Default::default();
+    let writer_lost = lost.clone();
+
+    let writer = tokio::task::Builder::new()
+        .name("job history writer")
+        .spawn(async move {
+            while let Some(update) = pending.recv().await {
+                let mut batch = vec![update];
+                while batch.len() < chan::MPSC_LEN {
+                    match pending.try_recv() {
+                        Ok(update) => batch.push(update),
+                        Err(_) => break,
+                    }
+                }
+                if let Err(err) = write(&pool, batch).await {
+                    println!("{err:#?}");
+                    *writer_lost.lock().unwrap() = Some(Utc::now());
+                }
+            }
+        });
+    if let Err(err) = writer {
+        println!("{err:#?}");
+        return;
+    }
+
+    let spawned = tokio::task::Builder::new()
+        .name("job history")
+        .spawn(async move {
+            loop {
+                let update = match rx.recv().await {
+                    Ok(Message::Health(data)) => {
+                        let before = lost.lock().unwrap().take();
+                        before.map(|before| Update::Reconcile {
+                            before,
+                            live: data
+                                .report
+                                .jobs
+                                .iter()
+                                .filter_map(|job_id| uuid::Uuid::parse_str(job_id).ok())
+                                .collect(),
+                        })
+                    }
+                    Ok(message) => Update::of(&message),
+                    Err(RecvError::Lagged(_)) => {
+                        *lost.lock().unwrap() = Some(Utc::now());
+                        None
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                if let Some(update) = update {
+                    if updates.send(update).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+    if let Err(err) = spawned {
+        println!("{err:#?}");
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct Filter {
+    pub page: Option<i64>,
+    pub limit: Option<i64>,
+    pub model: Option<ModelType>,
+    pub status: Option<JobStatus>,
+}
+
+fn database_error(err: sqlx::Error) -> (StatusCode, Json<ErrorResponse>) {
+    let json_error = ErrorResponse {
+        status: "fail",
+        message: format!("Error fetching jobs from database: {}", err),
+    };
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(json_error))
+}
+
+/// Jobs of the user, most recent first, `limit` per page.
+pub async fn list(
+    Extension(user): Extension<User>,
+    extract::State(state): extract::State<SharedState>,
+    extract::Query(filter): extract::Query<Filter>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ErrorResponse>)> {
+    let pool = state.read().await.pool.clone();
+    let limit = filter
+        .limit
+        .unwrap_or(page::LIMIT)
+        .clamp(1, page::MAX_LIMIT);
+    let page = filter.page.unwrap_or(1).max(1);
+
+    let jobs = sqlx::query_as!(
+        Job,
+        r#"SELECT * FROM jobs
+        WHERE user_id = $1
+            AND ($2::VARCHAR IS NULL OR model_type = $2)
+            AND ($3::VARCHAR IS NULL OR status = $3)
+        ORDER BY created_at DESC
+        LIMIT $4 OFFSET $5"#,
+        user.id,
+        filter.model.map(|model| format!("{model:?}")),
+        filter.status.map(|status| format!("{status:?}")),
+        limit,
+        (page - 1) * limit,
+    )
+    .fetch_all(pool.as_ref())
+    .await
+    .map_err(database_error)?;
+
+    Ok(Json(serde_json::json!({
+        "status": "success",
+        "data": serde_json::json!({
+            "jobs": jobs,
+            "page": page,
+            "limit": limit,
+        })
+    })))
+}
+
+pub async fn get(
+    Extension(user): Extension<User>,
+    extract::State(state): extract::State<SharedState>,
+    extract::Path(id): extract::Path<uuid::Uuid>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ErrorResponse>)> {
+    let pool = state.read().await.pool.clone();
+
+    let job = sqlx::query_as!(
+        Job,
+        "SELECT * FROM jobs WHERE id = $1 AND user_id = $2",
+        id,
+        user.id,
+    )
+    .fetch_optional(pool.as_ref())
+    .await
+    .map_err(database_error)?;
+
+    let job = job.ok_or_else(|| {
+        let json_error = ErrorResponse {
+            status: "fail",
+            message: "No job with this id".to_string(),
+        };
+        (StatusCode::NOT_FOUND, Json(json_error))
+    })?;
+
+    Ok(Json(serde_json::json!({
+        "status": "success",
+        "data": serde_json::json!({ "job": job })
+    })))
+}
@@ -0,0 +1,183 @@
+use axum::{extract, http::StatusCode, Extension, Json};
+use chrono::prelude::*;
+use shared::types::{CommandType, ModelType, Scope};
+
+use super::{route::SharedState, ErrorResponse};
+use crate::{
+    app::token,
+    db::model::{ApiKey, User},
+};
+
+type Pool = sqlx::Pool<sqlx::Postgres>;
+type Failure = (StatusCode, Json<ErrorResponse>);
+
+/// Start of every API key, telling them apart from JWTs in the
+/// `Authorization` header.
+pub const PREFIX: &str = "airs_";
+
+fn fail(status: StatusCode, message: &str) -> Failure {
+    let json_error = ErrorResponse {
+        status: "fail",
+        message: message.to_string(),
+    };
+    (status, Json(json_error))
+}
+
+fn database_error(err: sqlx::Error) -> Failure {
+    fail(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        &format!("Error fetching API keys from database: {}", err),
+    )
+}
+
+/// Keys are managed from a session, a key can not mint or revoke keys.
+fn session(scope: &Scope) -> Result<(), Failure> {
+    if scope.is_restricted() {
+        Err(fail(
+            StatusCode::FORBIDDEN,
+            "API keys can not manage API keys",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// The user of a key still in use, with the scope of the key.
+pub async fn authenticate(pool: &Pool, key: &str) -> Result<Option<(User, Scope)>, sqlx::Error> {
+    let api_key = sqlx::query_as!(
+        ApiKey,
+        r#"UPDATE api_keys SET last_used_at = NOW()
+        WHERE key_hash = $1 AND revoked_at IS NULL AND (expires_at IS NULL OR expires_at > NOW())
+        RETURNING *"#,
+        token::hash(key),
+    )
+    .fetch_optional(pool)
+    .await?;
+    let Some(api_key) = api_key else {
+        return Ok(None);
+    };
+
+    let user = sqlx::query_as!(User, "SELECT * FROM users WHERE id = $1", api_key.user_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(user.map(|user| (user, api_key.scope())))
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct KeyPayload {
+    pub name: String,
+    pub model_types: Vec<ModelType>,
+    pub command_types: Vec<CommandType>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Creates a key for the user. The key is only ever shown in this answer.
+pub async fn create(
+    Extension(user): Extension<User>,
+    Extension(scope): Extension<Scope>,
+    extract::State(state): extract::State<SharedState>,
+    Json(body): Json<KeyPayload>,
+) -> Result<(StatusCode, Json<serde_json::Value>), Failure> {
+    session(&scope)?;
+    if body.name.trim().is_empty() {
+        return Err(fail(StatusCode::UNPROCESSABLE_ENTITY, "A name is required"));
+    }
+    if body.model_types.is_empty() || body.command_types.is_empty() {
+        return Err(fail(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "At least one model type and one command type are required",
+        ));
+    }
+    if body
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= Utc::now())
+    {
+        return Err(fail(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "The expiry must be in the future",
+        ));
+    }
+    let pool = state.read().await.pool.clone();
+
+    let key = format!(
+        "{PREFIX}{}",
+        bs58::encode(rand::random::<[u8; 32]>()).into_string()
+    );
+    let api_key = sqlx::query_as!(
+        ApiKey,
+        r#"INSERT INTO api_keys (user_id, name, prefix, key_hash, model_types, command_types, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *"#,
+        user.id,
+        body.name.trim(),
+        &key[..PREFIX.len() + 6],
+        token::hash(&key),
+        serde_json::json!(body.model_types),
+        serde_json::json!(body.command_types),
+        body.expires_at,
+    )
+    .fetch_one(pool.as_ref())
+    .await
+    .map_err(database_error)?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(serde_json::json!({
+            "status": "success",
+            "data": serde_json::json!({
+                "key": key,
+                "apiKey": api_key,
+            })
+        })),
+    ))
+}
+
+/// Keys of the user, revoked and expired ones included, most recent first.
+pub async fn list(
+    Extension(user): Extension<User>,
+    Extension(scope): Extension<Scope>,
+    extract::State(state): extract::State<SharedState>,
+) -> Result<Json<serde_json::Value>, Failure> {
+    session(&scope)?;
+    let pool = state.read().await.pool.clone();
+
+    let api_keys = sqlx::query_as!(
+        ApiKey,
+        "SELECT * FROM api_keys WHERE user_id = $1 ORDER BY created_at DESC",
+        user.id,
+    )
+    .fetch_all(pool.as_ref())
+    .await
+    .map_err(database_error)?;
+
+    Ok(Json(serde_json::json!({
+        "status": "success",
+        "data": serde_json::json!({ "apiKeys": api_keys })
+    })))
+}
+
+pub async fn revoke(
+    Extension(user): Extension<User>,
+    Extension(scope): Extension<Scope>,
+    extract::State(state): extract::State<SharedState>,
+    extract::Path(id): extract::Path<uuid::Uuid>,
+) -> Result<Json<serde_json::Value>, Failure> {
+    session(&scope)?;
+    let pool = state.read().await.pool.clone();
+
+    let api_key = sqlx::query_as!(
+        ApiKey,
+        r#"UPDATE api_keys SET revoked_at = COALESCE(revoked_at, NOW())
+        WHERE id = $1 AND user_id = $2 RETURNING *"#,
+        id,
+        user.id,
+    )
+    .fetch_optional(pool.as_ref())
+    .await
+    .map_err(database_error)?;
+
+    let api_key = api_key.ok_or_else(|| fail(StatusCode::NOT_FOUND, "No API key with this id"))?;
+    Ok(Json(serde_json::json!({
+        "status": "success",
+        "data": serde_json::json!({ "apiKey": api_key })
+    })))
+}
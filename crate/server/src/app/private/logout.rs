@@ -1,23 +1,36 @@
-use axum::{
-    http::{header, Response, StatusCode},
-    response::IntoResponse,
-    Json,
-};
-use axum_extra::extract::cookie::{Cookie, SameSite};
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Extension, Json};
+use axum_extra::extract::cookie::CookieJar;
 use serde_json::json;
 
-pub async fn handler() -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let cookie = Cookie::build("token", "")
-        .path("/")
-        .max_age(time::Duration::hours(-1))
-        .secure(true)
-        .same_site(SameSite::None)
-        .http_only(false)
-        .finish();
+use super::route::SharedState;
+use crate::{app::token, db::model::TokenClaims};
 
-    let mut response = Response::new(json!({"status": "success"}).to_string());
-    response
-        .headers_mut()
-        .insert(header::SET_COOKIE, cookie.to_string().parse().unwrap());
-    Ok(response)
+/// Revokes the access token of the request and the refresh token of the
+/// cookie, then clears both cookies. API keys are revoked from `/api/keys`.
+pub async fn handler(
+    cookie_jar: CookieJar,
+    claims: Option<Extension<TokenClaims>>,
+    State(state): State<SharedState>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let pool = state.read().await.pool.clone();
+    let database_error = |e: sqlx::Error| {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("Database error: {}", e),
+        });
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+
+    if let Some(Extension(claims)) = claims {
+        token::revoke(pool.as_ref(), &claims)
+            .await
+            .map_err(database_error)?;
+    }
+    if let Some(cookie) = cookie_jar.get(token::REFRESH_COOKIE) {
+        token::revoke_refresh(pool.as_ref(), cookie.value())
+            .await
+            .map_err(database_error)?;
+    }
+
+    Ok(token::cleared())
 }
@@ -1,8 +1,15 @@
+pub mod admin;
 pub mod command;
+pub mod infer;
+pub mod jobs;
+pub mod keys;
 pub mod logout;
 pub mod profile;
+pub mod replay;
 pub mod route;
 pub mod sse;
+pub mod workers;
+pub mod ws;
 
 use axum::{
     extract::State,
@@ -15,9 +22,10 @@ use axum::{
 use axum_extra::extract::cookie::CookieJar;
 use jsonwebtoken::{decode, DecodingKey, Validation};
 use serde::Serialize;
+use shared::types::Scope;
 
 use crate::{
-    app::private::route::SharedState,
+    app::{private::route::SharedState, token},
     db::model::{TokenClaims, User},
 };
 
@@ -35,7 +43,7 @@ pub async fn auth<B>(
     next: Next<B>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<ErrorResponse>)> {
     let token = cookie_jar
-        .get("token")
+        .get(token::ACCESS_COOKIE)
         .map(|cookie| cookie.value().to_string())
         .or_else(|| {
             req.headers()
@@ -58,6 +66,39 @@ pub async fn auth<B>(
         (StatusCode::UNAUTHORIZED, Json(json_error))
     })?;
 
+    if token.starts_with(keys::PREFIX) {
+        let pool = data.read().await.pool.clone();
+        let authenticated = keys::authenticate(pool.as_ref(), &token)
+            .await
+            .map_err(|e| {
+                let json_error = ErrorResponse {
+                    status: "fail",
+                    message: format!("Error fetching API key from database: {}", e),
+                };
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(json_error))
+            })?;
+
+        let (user, scope) = authenticated.ok_or_else(|| {
+            let json_error = ErrorResponse {
+                status: "fail",
+                message: "Invalid, expired or revoked API key".to_string(),
+            };
+            (StatusCode::UNAUTHORIZED, Json(json_error))
+        })?;
+
+        if user.disabled {
+            let json_error = ErrorResponse {
+                status: "fail",
+                message: "This account is disabled".to_string(),
+            };
+            return Err((StatusCode::FORBIDDEN, Json(json_error)));
+        }
+
+        req.extensions_mut().insert(user);
+        req.extensions_mut().insert(scope);
+        return Ok(next.run(req).await);
+    }
+
     let config = &data.read().await.config;
     let config = config.clone();
 
@@ -105,6 +146,32 @@ pub async fn auth<B>(
         (StatusCode::UNAUTHORIZED, Json(json_error))
     })?;
 
+    if user.disabled {
+        let json_error = ErrorResponse {
+            status: "fail",
+            message: "This account is disabled".to_string(),
+        };
+        return Err((StatusCode::FORBIDDEN, Json(json_error)));
+    }
+
+    let revoked = token::revoked(pool.as_ref(), &claims).await.map_err(|e| {
+        let json_error = ErrorResponse {
+            status: "fail",
+            message: format!("Error fetching revoked tokens from database: {}", e),
+        };
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(json_error))
+    })?;
+
+    if revoked || user.revoked(claims.iat) {
+        let json_error = ErrorResponse {
+            status: "fail",
+            message: "Token revoked, please log in again".to_string(),
+        };
+        return Err((StatusCode::UNAUTHORIZED, Json(json_error)));
+    }
+
     req.extensions_mut().insert(user);
+    req.extensions_mut().insert(claims);
+    req.extensions_mut().insert(Scope::full());
     Ok(next.run(req).await)
 }
@@ -0,0 +1,263 @@
+use shared::{
+    constants::time,
+    message::{Message, Owner},
+    types::{Role, Scope},
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::{
+    broadcast::{self, error::RecvError},
+    RwLock,
+};
+
+use crate::db::model::User;
+
+/// What an SSE event carries: a bus message, or the count of bus messages
+/// the sequencer fell behind on, whose owners are unknown.
+#[derive(Debug, Clone)]
+pub enum Payload {
+    Message(Message),
+    Lagged(u64),
+}
+
+/// A bus message with the id of its SSE event. Ids grow by one per message
+/// and start over when the server restarts.
+#[derive(Debug, Clone)]
+pub struct Sequenced {
+    pub id: u64,
+    pub payload: Payload,
+}
+
+/// Whether health reports reach the user whole; other users get them
+/// `Message::redacted`, without the jobs and workers of others.
+pub fn sees_all(user: &User, scope: &Scope) -> bool {
+    user.role() == Role::Admin && !scope.is_restricted()
+}
+
+impl Sequenced {
+    /// Health reports and lost messages go to every owner.
+    pub fn is_for(&self, owner: &str) -> bool {
+        match &self.payload {
+            Payload::Message(message) => message.owner() == owner || message.is_health(),
+            Payload::Lagged(_) => true,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Buffer {
+    events: VecDeque<Arc<Sequenced>>,
+    touched: Instant,
+}
+
+type Buffers = Arc<RwLock<HashMap<String, Buffer>>>;
+
+/// Numbers the bus messages and keeps the last `capacity` ones of each
+/// owner, so that a client reconnecting with `Last-Event-ID` gets what it
+/// missed. Health reports are numbered but not kept. Messages the sequencer
+/// falls behind on are kept as one `Lagged` event in every buffer, and the
+/// buffers of owners without a message for `time::REPLAY_IDLE` are dropped.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    tx: broadcast::Sender<Arc<Sequenced>>,
+    buffers: Buffers,
+    capacity: usize,
+}
+
+impl Replay {
+    pub fn new(bus: &broadcast::Sender<Message>, capacity: usize, bordcast_size: usize) -> Self {
+        let (tx, _unused) = broadcast::channel(bordcast_size);
+        let buffers = Buffers::default();
+        let replay = Self {
+            tx,
+            buffers,
+            capacity,
+        };
+
+        let mut rx = bus.subscribe();
+        let sequencer = replay.clone();
+        let idle = Duration::from_millis(time::REPLAY_IDLE);
+        let spawned = tokio::task::Builder::new()
+            .name("sse replay")
+            .spawn(async move {
+                let mut id = 0;
+                let mut pruned = Instant::now();
+                loop {
+                    let payload = match rx.recv().await {
+                        Ok(message) => Payload::Message(message),
+                        Err(RecvError::Lagged(missed)) => Payload::Lagged(missed),
+                        Err(RecvError::Closed) => break,
+                    };
+                    id += 1;
+                    sequencer.push(Arc::new(Sequenced { id, payload })).await;
+                    if pruned.elapsed() >= idle {
+                        sequencer.prune(idle).await;
+                        pruned = Instant::now();
+                    }
+                }
+            });
+
+        if let Err(err) = spawned {
+            println!("{err:#?}");
+        }
+        replay
+    }
+
+    async fn push(&self, sequenced: Arc<Sequenced>) {
+        let keep = |buffer: &mut Buffer| {
+            if buffer.events.len() >= self.capacity {
+                buffer.events.pop_front();
+            }
+            buffer.events.push_back(sequenced.clone());
+        };
+        match &sequenced.payload {
+            Payload::Message(message) if message.is_health() => (),
+            Payload::Message(message) => {
+                let mut buffers = self.buffers.write().await;
+                let buffer = buffers
+                    .entry(message.owner().to_owned())
+                    .or_insert_with(|| Buffer {
+                        events: VecDeque::new(),
+                        touched: Instant::now(),
+                    });
+                buffer.touched = Instant::now();
+                keep(buffer);
+            }
+            Payload::Lagged(_) => self.buffers.write().await.values_mut().for_each(keep),
+        }
+        // No subscriber is not an error, the event stays in the buffer.
+        let _ = self.tx.send(sequenced);
+    }
+
+    /// Drops the buffers of owners without a message for `idle`.
+    async fn prune(&self, idle: Duration) {
+        self.buffers
+            .write()
+            .await
+            .retain(|_, buffer| buffer.touched.elapsed() < idle);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<Sequenced>> {
+        self.tx.subscribe()
+    }
+
+    /// Kept events of the owner after `last_id`, oldest first.
+    pub async fn since(&self, owner: &str, last_id: u64) -> Vec<Arc<Sequenced>> {
+        self.buffers
+            .read()
+            .await
+            .get(owner)
+            .map(|buffer| {
+                buffer
+                    .events
+                    .iter()
+                    .filter(|sequenced| sequenced.id > last_id)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Buffers, Payload, Replay, Sequenced};
+    use shared::{
+        message::{HealthT, JobQueuedT, Message},
+        types::{HealthReport, MessageType, ModelType},
+    };
+    use std::{sync::Arc, time::Duration};
+    use tokio::sync::broadcast;
+
+    fn replay(capacity: usize) -> Replay {
+        let (tx, _unused) = broadcast::channel(16);
+        Replay {
+            tx,
+            buffers: Buffers::default(),
+            capacity,
+        }
+    }
+
+    fn queued(owner: &str) -> Payload {
+        Payload::Message(Message::JobQueued(JobQueuedT {
+            owner: owner.to_owned(),
+            timestamp: 0,
+            job_id: "job".to_owned(),
+            message_type: MessageType::JobQueued,
+            model_type: ModelType::Sentiment,
+            task_id: "1".to_owned(),
+        }))
+    }
+
+    fn health() -> Payload {
+        Payload::Message(Message::Health(HealthT {
+            owner: "ROOT".to_owned(),
+            message_type: MessageType::Health,
+            timestamp: 0,
+            job_id: None,
+            task_id: "1".to_owned(),
+            report: HealthReport::default(),
+        }))
+    }
+
+    async fn push(replay: &Replay, id: u64, payload: Payload) {
+        replay.push(Arc::new(Sequenced { id, payload })).await;
+    }
+
+    fn ids(events: Vec<Arc<Sequenced>>) -> Vec<u64> {
+        events.iter().map(|sequenced| sequenced.id).collect()
+    }
+
+    #[tokio::test]
+    async fn capacity() {
+        let replay = replay(3);
+        for id in 1..=5 {
+            push(&replay, id, queued("alice")).await;
+        }
+        assert_eq!(ids(replay.since("alice", 0).await), vec![3, 4, 5]);
+    }
+
+    #[tokio::test]
+    async fn since() {
+        let replay = replay(10);
+        push(&replay, 1, queued("alice")).await;
+        push(&replay, 2, queued("bob")).await;
+        push(&replay, 3, health()).await;
+        push(&replay, 4, queued("alice")).await;
+        push(&replay, 5, queued("alice")).await;
+
+        assert_eq!(ids(replay.since("alice", 0).await), vec![1, 4, 5]);
+        assert_eq!(ids(replay.since("alice", 1).await), vec![4, 5]);
+        assert_eq!(ids(replay.since("alice", 5).await), Vec::<u64>::new());
+        assert_eq!(ids(replay.since("bob", 0).await), vec![2]);
+        assert_eq!(ids(replay.since("carol", 0).await), Vec::<u64>::new());
+    }
+
+    #[tokio::test]
+    async fn lagged() {
+        let replay = replay(10);
+        push(&replay, 1, queued("alice")).await;
+        push(&replay, 2, queued("bob")).await;
+        push(&replay, 3, Payload::Lagged(7)).await;
+
+        for owner in ["alice", "bob"] {
+            let events = replay.since(owner, 0).await;
+            assert_eq!(ids(events.clone()).last(), Some(&3));
+            assert!(matches!(events[1].payload, Payload::Lagged(7)));
+            assert!(events[1].is_for(owner));
+        }
+    }
+
+    #[tokio::test]
+    async fn prune() {
+        let replay = replay(10);
+        push(&replay, 1, queued("alice")).await;
+        replay.prune(Duration::from_secs(60)).await;
+        assert_eq!(ids(replay.since("alice", 0).await), vec![1]);
+        replay.prune(Duration::ZERO).await;
+        assert!(replay.since("alice", 0).await.is_empty());
+    }
+}
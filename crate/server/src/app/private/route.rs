@@ -1,17 +1,17 @@
 use axum::{
     middleware,
-    routing::{get, post},
+    routing::{delete, get, patch, post},
 };
 use shared::constants::route;
 use std::sync::Arc;
 use tokio::sync::broadcast;
-use tokio::sync::RwLock;
+use tokio::sync::{watch, RwLock};
 use tower_http::services::ServeDir;
 
 pub type SharedState = Arc<RwLock<State>>;
-use shared::{command::Command, message::Message};
+use shared::{command::Command, config::Application, message::Message};
 
-use super::{command, sse};
+use super::{admin, command, infer, jobs, keys, replay, sse, workers, ws};
 use crate::db;
 
 #[derive(Debug)]
@@ -20,6 +20,10 @@ pub struct State {
     pub tx: broadcast::Sender<Message>,
     pub pool: Arc<sqlx::Pool<sqlx::Postgres>>,
     pub config: db::Config,
+    pub health: workers::SharedHealth,
+    pub replay: replay::Replay,
+    pub closing: watch::Receiver<bool>,
+    pub application: watch::Receiver<Application>,
 }
 
 impl State {
@@ -28,12 +32,23 @@ impl State {
         tx: broadcast::Sender<Message>,
         pool: Arc<sqlx::Pool<sqlx::Postgres>>,
         config: db::Config,
+        closing: watch::Receiver<bool>,
+        application: watch::Receiver<Application>,
     ) -> Self {
+        let health = workers::SharedHealth::default();
+        workers::cache(&tx, health.clone());
+        jobs::record(&tx, pool.clone());
+        let channel = application.borrow().channel.clone();
+        let replay = replay::Replay::new(&tx, channel.replay_size, channel.bordcast_size);
         Self {
             http_tx,
             tx,
             pool,
             config,
+            health,
+            replay,
+            closing,
+            application,
         }
     }
 }
@@ -43,11 +58,21 @@ pub async fn build(
     tx: broadcast::Sender<Message>,
     pool: Arc<sqlx::Pool<sqlx::Postgres>>,
     config: db::Config,
+    closing: watch::Receiver<bool>,
+    application: watch::Receiver<Application>,
 ) -> axum::Router {
-    let state: SharedState = Arc::new(RwLock::new(State::new(http_tx, tx, pool, config)));
+    let state: SharedState = Arc::new(RwLock::new(State::new(
+        http_tx,
+        tx,
+        pool,
+        config,
+        closing,
+        application,
+    )));
 
     let routes = axum::Router::new()
         .route(route::SSE_URL, get(sse::handler))
+        .route(route::WS_URL, get(ws::handler))
         .route(
             route::API_COMMAND_PROCESS_URL,
             post(command::handler::<command::playload::Process>),
@@ -56,10 +81,33 @@ pub async fn build(
             route::API_COMMAND_KILL_URL,
             post(command::handler::<command::playload::Kill>),
         )
+        .route(
+            route::API_COMMAND_PAUSE_URL,
+            post(command::handler::<command::playload::Pause>),
+        )
+        .route(
+            route::API_COMMAND_RESUME_URL,
+            post(command::handler::<command::playload::Resume>),
+        )
         .route(
             route::API_COMMAND_SPAWN_URL,
             post(command::handler::<command::playload::Spawn>),
         )
+        .route(
+            route::API_COMMAND_CANCEL_URL,
+            post(command::handler::<command::playload::Cancel>),
+        )
+        .route(route::API_WORKERS_URL, get(workers::handler))
+        .route(route::API_JOBS_URL, get(jobs::list))
+        .route(route::API_JOB_URL, get(jobs::get))
+        .route(route::API_V1_URL, post(infer::handler))
+        .route(route::API_KEYS_URL, get(keys::list).post(keys::create))
+        .route(route::API_KEY_URL, delete(keys::revoke))
+        .route(route::API_ADMIN_USERS_URL, get(admin::list))
+        .route(route::API_ADMIN_USER_ROLE_URL, patch(admin::role))
+        .route(route::API_ADMIN_USER_VERIFIED_URL, patch(admin::verified))
+        .route(route::API_ADMIN_USER_DISABLED_URL, patch(admin::disabled))
+        .route(route::API_ADMIN_USER_REVOKE_URL, post(admin::revoke))
         .route("/logout", get(super::logout::handler))
         .route("/profile", get(super::profile::handler))
         .route_layer(middleware::from_fn_with_state(state.clone(), super::auth))
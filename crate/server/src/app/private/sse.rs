@@ -1,25 +1,74 @@
-use shared::message::Owner;
-
 use axum::{
     extract,
+    http::HeaderMap,
     response::sse::{Event, KeepAlive, Sse},
     Extension,
 };
 use futures::stream::Stream;
+use shared::types::Scope;
+
+use crate::{db::model::User, shutdown};
+use tokio_stream::{
+    wrappers::{errors::BroadcastStreamRecvError, BroadcastStream},
+    StreamExt,
+};
 
-use crate::db::model::User;
-use tokio_stream::{wrappers::BroadcastStream, StreamExt};
+use super::{
+    replay::{self, Payload, Sequenced},
+    route::SharedState,
+};
 
-use super::route::SharedState;
+/// The event of a sequenced message, redacted for `viewer` unless `None`.
+fn event(sequenced: &Sequenced, viewer: Option<&str>) -> Result<Event, serde_json::Error> {
+    let event = Event::default().id(sequenced.id.to_string());
+    match &sequenced.payload {
+        Payload::Message(message) => match viewer {
+            Some(viewer) => event.json_data(message.redacted(viewer)),
+            None => event.json_data(message),
+        },
+        Payload::Lagged(missed) => Ok(event.event("Lagged").data(missed.to_string())),
+    }
+}
 
+/// Streams the messages of the user. With a `Last-Event-ID` header, the
+/// kept events that came after it are sent first. A client falling behind
+/// the bus, or messages the replay fell behind on, give a `Lagged` event
+/// with the count of messages missed. Health reports are redacted for users
+/// who are not admins.
 pub async fn handler(
     Extension(user): Extension<User>,
+    Extension(scope): Extension<Scope>,
     extract::State(state): extract::State<SharedState>,
+    headers: HeaderMap,
 ) -> Sse<impl Stream<Item = Result<Event, serde_json::Error>>> {
+    let viewer = (!replay::sees_all(&user, &scope)).then(|| user.pubkey.clone());
     let user_id = user.pubkey;
-    let stream = BroadcastStream::new(state.read().await.tx.subscribe())
-        .map(|value| value.ok().unwrap())
-        .filter(move |message| Owner::owner(message) == user_id || message.is_health())
-        .map(|data| Event::default().json_data(data));
+    let last_id = headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok());
+
+    let state = state.read().await;
+    let rx = state.replay.subscribe();
+    let replayed = match last_id {
+        Some(last_id) => state.replay.since(&user_id, last_id).await,
+        None => Vec::new(),
+    };
+    // Events kept while subscribing are both replayed and received live.
+    let floor = replayed.last().map(|sequenced| sequenced.id).unwrap_or(0);
+
+    let replayed_viewer = viewer.clone();
+    let replayed = tokio_stream::iter(replayed)
+        .map(move |sequenced| event(&sequenced, replayed_viewer.as_deref()));
+    let live = BroadcastStream::new(rx).filter_map(move |value| match value {
+        Ok(sequenced) => (sequenced.is_for(&user_id) && sequenced.id > floor)
+            .then(|| event(&sequenced, viewer.as_deref())),
+        Err(BroadcastStreamRecvError::Lagged(missed)) => Some(Ok(Event::default()
+            .event("Lagged")
+            .data(missed.to_string()))),
+    });
+
+    let stream = replayed.chain(live);
+    let stream = futures::StreamExt::take_until(stream, shutdown::closed(state.closing.clone()));
     Sse::new(stream).keep_alive(KeepAlive::default())
 }
@@ -0,0 +1,38 @@
+use axum::{extract, Json};
+use std::sync::Arc;
+use tokio::sync::{
+    broadcast::{self, error::RecvError},
+    RwLock,
+};
+
+use shared::{message::Message, types::HealthReport};
+
+use super::route::SharedState;
+
+pub type SharedHealth = Arc<RwLock<HealthReport>>;
+
+/// Keeps the last health report broadcast by the supervisor.
+pub fn cache(tx: &broadcast::Sender<Message>, health: SharedHealth) {
+    let mut rx = tx.subscribe();
+    let spawned = tokio::task::Builder::new()
+        .name("health cache")
+        .spawn(async move {
+            loop {
+                match rx.recv().await {
+                    Ok(Message::Health(data)) => *health.write().await = data.report,
+                    Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+
+    if let Err(err) = spawned {
+        println!("{err:#?}");
+    }
+}
+
+pub async fn handler(extract::State(state): extract::State<SharedState>) -> Json<HealthReport> {
+    let health = state.read().await.health.clone();
+    let report = health.read().await.clone();
+    Json(report)
+}
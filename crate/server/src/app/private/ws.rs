@@ -0,0 +1,224 @@
+use axum::{
+    extract::{
+        ws::{Message as Frame, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::IntoResponse,
+    Extension,
+};
+use futures::{SinkExt, StreamExt};
+use shared::{
+    constants::time,
+    message::{Message, Owner},
+    types::{CommandType, MessageType, ModelType, Scope},
+};
+use std::{borrow::Cow, time::Duration};
+use tokio::sync::broadcast::error::RecvError;
+
+use super::command::{self, playload};
+use super::{replay, route::SharedState};
+use crate::{db::model::User, shutdown};
+
+/// Narrows the messages pushed on a connection. An empty list lets every
+/// value through, a message must match all the non empty ones.
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize)]
+pub struct Subscription {
+    #[serde(default)]
+    pub job_ids: Vec<String>,
+    #[serde(default)]
+    pub model_types: Vec<ModelType>,
+    #[serde(default)]
+    pub message_types: Vec<MessageType>,
+}
+
+impl Subscription {
+    fn matches(&self, message: &Message) -> bool {
+        let job_id = message.job_id();
+        let model_type = message.model_type();
+        (self.job_ids.is_empty() || job_id.is_some_and(|id| self.job_ids.contains(&id)))
+            && (self.model_types.is_empty()
+                || model_type.is_some_and(|model| self.model_types.contains(&model)))
+            && (self.message_types.is_empty()
+                || self.message_types.contains(&message.message_type()))
+    }
+}
+
+/// Frames sent by the client, commands take the payloads of `/api/command`.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Request {
+    Process(playload::Process),
+    Kill(playload::Kill),
+    Pause(playload::Pause),
+    Resume(playload::Resume),
+    Spawn(playload::Spawn),
+    Cancel(playload::Cancel),
+    Subscribe(Subscription),
+}
+
+/// Answer to a request frame, pushed before the messages it leads to.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Reply {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command_type: Option<CommandType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Reply {
+    fn error(error: String) -> Self {
+        Self {
+            command_type: None,
+            job_id: None,
+            error: Some(error),
+        }
+    }
+}
+
+async fn submit<T>(state: &SharedState, user: &User, scope: &Scope, payload: T) -> Reply
+where
+    T: playload::Playload + std::fmt::Debug,
+{
+    let command_type = Some(payload.command_type());
+    // The frame is read by its variant, its `command_type` has to agree.
+    if payload.declared_command_type() != payload.command_type() {
+        return Reply {
+            command_type,
+            job_id: None,
+            error: Some(
+                command::Refusal::Mismatch {
+                    declared: payload.declared_command_type(),
+                    expected: payload.command_type(),
+                }
+                .to_string(),
+            ),
+        };
+    }
+
+    let state = state.read().await;
+    match command::submit(&state, user, scope, &payload).await {
+        Ok(source) => Reply {
+            command_type,
+            job_id: source.job_id(),
+            error: None,
+        },
+        Err(error) => Reply {
+            command_type,
+            job_id: None,
+            error: Some(error.to_string()),
+        },
+    }
+}
+
+async fn request(
+    state: &SharedState,
+    user: &User,
+    scope: &Scope,
+    subscription: &mut Subscription,
+    text: &str,
+) -> Reply {
+    let request = match serde_json::from_str::<Request>(text) {
+        Ok(request) => request,
+        Err(err) => return Reply::error(err.to_string()),
+    };
+
+    match request {
+        Request::Process(payload) => submit(state, user, scope, payload).await,
+        Request::Kill(payload) => submit(state, user, scope, payload).await,
+        Request::Pause(payload) => submit(state, user, scope, payload).await,
+        Request::Resume(payload) => submit(state, user, scope, payload).await,
+        Request::Spawn(payload) => submit(state, user, scope, payload).await,
+        Request::Cancel(payload) => submit(state, user, scope, payload).await,
+        Request::Subscribe(next) => {
+            *subscription = next;
+            Reply {
+                command_type: None,
+                job_id: None,
+                error: None,
+            }
+        }
+    }
+}
+
+fn text<T: serde::Serialize>(value: &T) -> Frame {
+    Frame::Text(serde_json::json!(value).to_string())
+}
+
+/// Serves one connection until the client leaves, misses a keepalive ping or
+/// the server closes.
+async fn connection(socket: WebSocket, user: User, scope: Scope, state: SharedState) {
+    let (mut sender, mut receiver) = socket.split();
+    let (mut rx, closing) = {
+        let state = state.read().await;
+        (state.tx.subscribe(), state.closing.clone())
+    };
+    let closed = shutdown::closed(closing);
+    tokio::pin!(closed);
+
+    let sees_all = replay::sees_all(&user, &scope);
+    let mut subscription = Subscription::default();
+    let mut keepalive = tokio::time::interval(Duration::from_millis(time::KEEPALIVE));
+    let mut alive = true;
+
+    loop {
+        tokio::select! {
+            _ = &mut closed => break,
+            _ = keepalive.tick() => {
+                if !alive {
+                    break;
+                }
+                alive = false;
+                if sender.send(Frame::Ping(Vec::new())).await.is_err() {
+                    break;
+                }
+            }
+            frame = receiver.next() => {
+                alive = true;
+                match frame {
+                    Some(Ok(Frame::Text(value))) => {
+                        let reply = request(&state, &user, &scope, &mut subscription, &value).await;
+                        if sender.send(text(&serde_json::json!({ "reply": reply }))).await.is_err() {
+                            break;
+                        }
+                    }
+                    Some(Ok(Frame::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => continue,
+                }
+            }
+            message = rx.recv() => match message {
+                Ok(message) => {
+                    let owned = message.owner() == user.pubkey || message.is_health();
+                    let message = if sees_all {
+                        Cow::Borrowed(&message)
+                    } else {
+                        message.redacted(&user.pubkey)
+                    };
+                    if owned && subscription.matches(&message)
+                        && sender.send(text(&message)).await.is_err()
+                    {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            },
+        }
+    }
+
+    if let Err(err) = sender.close().await {
+        println!("{err:#?}");
+    }
+}
+
+/// Carries commands from the client and the messages of its owner back, as
+/// JSON text frames.
+pub async fn handler(
+    ws: WebSocketUpgrade,
+    Extension(user): Extension<User>,
+    Extension(scope): Extension<Scope>,
+    State(state): State<SharedState>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| connection(socket, user, scope, state))
+}
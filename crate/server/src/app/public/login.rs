@@ -1,110 +1,128 @@
 use std::sync::Arc;
 
-use axum::{
-    http::{header, Response, StatusCode},
-    Extension, Json,
+use axum::{http::StatusCode, Extension, Json};
+use shared::constants::time;
+
+use crate::{app::token, db::model::User};
+
+use super::{
+    route::State,
+    siws::{self, SignIn},
+    AuthPayload,
 };
-use axum_extra::extract::cookie::{Cookie, SameSite};
-use jsonwebtoken::{encode, EncodingKey, Header};
-use serde_json::json;
 
-use crate::db::model::{TokenClaims, User};
+type Failure = (StatusCode, Json<serde_json::Value>);
+
+fn fail(status: StatusCode, message: String) -> Failure {
+    let error_response = serde_json::json!({
+        "status": "fail",
+        "message": message,
+    });
+    (status, Json(error_response))
+}
+
+/// Ed25519 keys and signatures, of `length` bytes once decoded.
+fn decode(value: &str, field: &str, length: usize) -> Result<Vec<u8>, Failure> {
+    bs58::decode(value)
+        .into_vec()
+        .ok()
+        .filter(|bytes| bytes.len() == length)
+        .ok_or_else(|| fail(StatusCode::BAD_REQUEST, format!("Invalid base58 {field}")))
+}
 
-use super::{route::State, AuthPayload};
+/// Issues the nonce to put in the next sign in message.
+pub(crate) async fn nonce(Extension(state): Extension<Arc<State>>) -> Json<serde_json::Value> {
+    let ttl = chrono::Duration::milliseconds(time::NONCE_TTL as i64);
+    let (nonce, expires_at) = siws::issue(state.env.jwt_secret.as_bytes(), ttl);
 
+    Json(serde_json::json!({
+        "status": "success",
+        "nonce": nonce,
+        "expiresAt": expires_at,
+    }))
+}
+
+/// Signs the user in with a Sign-In-With-Solana message signed by its
+/// wallet. The message must be for one of the allowed origins, still valid,
+/// and carry an unused nonce from `/nonce`.
 pub(crate) async fn handler(
     token: csrf::CsrfToken,
     Extension(state): Extension<Arc<State>>,
     body: axum::Json<AuthPayload>,
-) -> Result<impl axum::response::IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    if token.verify(&body.token).is_ok() {
-        let msg = format!("{}{}", body.message, body.token);
-        let signature = bs58::decode(&body.signature).into_vec().ok().unwrap();
-        let pubkey = bs58::decode(&body.pubkey).into_vec().ok().unwrap();
+) -> Result<impl axum::response::IntoResponse, Failure> {
+    if token.verify(&body.token).is_err() {
+        return Err(fail(StatusCode::BAD_REQUEST, "Invalid csrf".to_string()));
+    }
 
-        if !nacl::sign::verify(&signature, msg.as_bytes(), &pubkey)
-            .ok()
-            .unwrap_or(false)
-        {
-            let error_response = serde_json::json!({
-                "status": "error",
-                "message": format!("Cannot verify msg token."),
-            });
-            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
-        } else {
-            let user = sqlx::query_as!(
-                User,
-                "SELECT * FROM users WHERE pubkey = $1;",
-                body.pubkey.to_string()
-            )
-            .fetch_optional(state.db.as_ref())
-            .await
-            .map_err(|e| {
-                let error_response = serde_json::json!({
-                    "status": "error",
-                    "message": format!("Database error: {}", e),
-                });
-                (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
-            })?;
+    let signature = decode(&body.signature, "signature", 64)?;
+    let pubkey = decode(&body.pubkey, "pubkey", 32)?;
+    let sign_in = body
+        .message
+        .parse::<SignIn>()
+        .map_err(|refusal| fail(refusal.status(), refusal.to_string()))?;
+    let origins = state
+        .application
+        .borrow()
+        .server
+        .cors_allowed_origins
+        .clone();
+    sign_in
+        .check(&origins, &body.pubkey)
+        .map_err(|refusal| fail(refusal.status(), refusal.to_string()))?;
 
-            let id = match user {
-                Some(user) => user.id,
-                None => {
-                    let user = sqlx::query_as!(
-                        User,
-                        "INSERT INTO users (pubkey) VALUES ($1) RETURNING *",
-                        body.pubkey.to_string(),
-                    )
-                    .fetch_one(state.db.as_ref())
-                    .await
-                    .map_err(|e| {
-                        let error_response = serde_json::json!({
-                            "status": "fail",
-                            "message": format!("Database error: {}", e),
-                        });
-                        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
-                    })?;
-                    user.id
-                }
-            };
+    if !nacl::sign::verify(&signature, body.message.as_bytes(), &pubkey).unwrap_or(false) {
+        return Err(fail(
+            StatusCode::UNAUTHORIZED,
+            "Cannot verify the signature of the message".to_string(),
+        ));
+    }
+    siws::consume(
+        state.db.as_ref(),
+        state.env.jwt_secret.as_bytes(),
+        &sign_in.nonce,
+    )
+    .await
+    .map_err(|refusal| fail(refusal.status(), refusal.to_string()))?;
 
-            let now = chrono::Utc::now();
-            let iat = now.timestamp() as usize;
-            let exp = (now + chrono::Duration::minutes(60)).timestamp() as usize;
-            let claims: TokenClaims = TokenClaims {
-                sub: id.to_string(),
-                exp,
-                iat,
-            };
+    let database_error = |e: sqlx::Error| {
+        fail(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database error: {}", e),
+        )
+    };
+    let user = sqlx::query_as!(
+        User,
+        "SELECT * FROM users WHERE pubkey = $1;",
+        body.pubkey.to_string()
+    )
+    .fetch_optional(state.db.as_ref())
+    .await
+    .map_err(database_error)?;
 
-            let token = encode(
-                &Header::default(),
-                &claims,
-                &EncodingKey::from_secret(state.env.jwt_secret.as_ref()),
+    let id = match user {
+        Some(user) if user.disabled => {
+            return Err(fail(
+                StatusCode::FORBIDDEN,
+                "This account is disabled".to_string(),
+            ));
+        }
+        Some(user) => user.id,
+        None => {
+            let user = sqlx::query_as!(
+                User,
+                "INSERT INTO users (pubkey) VALUES ($1) RETURNING *",
+                body.pubkey.to_string(),
             )
-            .unwrap();
-
-            let cookie = Cookie::build("token", token.to_owned())
-                .path("/")
-                .max_age(time::Duration::hours(1))
-                .secure(true)
-                .same_site(SameSite::None)
-                .http_only(false)
-                .finish();
+            .fetch_one(state.db.as_ref())
+            .await
+            .map_err(database_error)?;
+            user.id
+        }
+    };
 
-            let mut response =
-                Response::new(json!({"status": "success", "token": token}).to_string());
-            response
-                .headers_mut()
-                .insert(header::SET_COOKIE, cookie.to_string().parse().unwrap());
+    let tokens = token::issue(state.db.as_ref(), &state.env, id)
+        .await
+        .map_err(database_error)?;
 
-            Ok(response)
-        }
-    } else {
-        let error_response = serde_json::json!({
-          "status": "fail",
-          "message": "Invalid csrf",
-        });
-        Err((StatusCode::BAD_REQUEST, Json(error_response)))
-    }
+    Ok(token::response(&state.env, tokens))
 }
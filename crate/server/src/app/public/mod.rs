@@ -1,5 +1,7 @@
 pub mod login;
+pub mod refresh;
 pub mod route;
+pub mod siws;
 
 #[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 pub(crate) struct AuthPayload {
@@ -0,0 +1,47 @@
+use std::sync::Arc;
+
+use axum::{http::StatusCode, Extension, Json};
+use axum_extra::extract::cookie::CookieJar;
+
+use crate::app::token;
+
+use super::route::State;
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub(crate) struct RefreshPayload {
+    pub refresh_token: String,
+}
+
+/// Trades the refresh token of the cookie, or of the body, for a new pair.
+pub(crate) async fn handler(
+    cookie_jar: CookieJar,
+    Extension(state): Extension<Arc<State>>,
+    body: Option<Json<RefreshPayload>>,
+) -> Result<impl axum::response::IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let refresh_token = body
+        .map(|Json(body)| body.refresh_token)
+        .or_else(|| {
+            cookie_jar
+                .get(token::REFRESH_COOKIE)
+                .map(|cookie| cookie.value().to_string())
+        })
+        .ok_or_else(|| {
+            let error_response = serde_json::json!({
+                "status": "fail",
+                "message": "No refresh token provided",
+            });
+            (StatusCode::UNAUTHORIZED, Json(error_response))
+        })?;
+
+    let tokens = token::rotate(state.db.as_ref(), &state.env, &refresh_token)
+        .await
+        .map_err(|refusal| {
+            let error_response = serde_json::json!({
+                "status": "fail",
+                "message": refusal.to_string(),
+            });
+            (refusal.status(), Json(error_response))
+        })?;
+
+    Ok(token::response(&state.env, tokens))
+}
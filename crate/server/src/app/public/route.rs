@@ -4,18 +4,26 @@ use axum::{
     routing::{get, post},
     Extension,
 };
+use shared::config::Application;
+use tokio::sync::watch;
 
 #[derive(Debug)]
 pub struct State {
     pub db: Arc<sqlx::Pool<sqlx::Postgres>>,
     pub env: crate::db::Config,
+    pub application: watch::Receiver<Application>,
 }
 
 impl State {
-    pub fn new(pool: Arc<sqlx::Pool<sqlx::Postgres>>, config: crate::db::Config) -> Arc<Self> {
+    pub fn new(
+        pool: Arc<sqlx::Pool<sqlx::Postgres>>,
+        config: crate::db::Config,
+        application: watch::Receiver<Application>,
+    ) -> Arc<Self> {
         Arc::new(Self {
             db: pool,
             env: config,
+            application,
         })
     }
 }
@@ -27,8 +35,9 @@ async fn health() -> impl axum::response::IntoResponse {
 pub async fn build(
     pool: Arc<sqlx::Pool<sqlx::Postgres>>,
     config: crate::db::Config,
+    application: watch::Receiver<Application>,
 ) -> axum::Router {
-    let state = State::new(pool, config);
+    let state = State::new(pool, config, application);
 
     let key = std::option_env!("CSRF_KEY").expect("CSRF_KEY env not set");
     let key = csrf::Key::from(key.as_bytes());
@@ -40,7 +49,9 @@ pub async fn build(
 
     axum::Router::new()
         .route("/health", get(health))
+        .route("/nonce", get(super::login::nonce))
         .route("/login", post(super::login::handler))
+        .route("/refresh", post(super::refresh::handler))
         .route("/", get(super::handler))
         .layer(Extension(state))
         .with_state(config)
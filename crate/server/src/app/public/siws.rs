@@ -0,0 +1,449 @@
+use axum::http::StatusCode;
+use chrono::prelude::*;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type Pool = sqlx::Pool<sqlx::Postgres>;
+
+const HEADER: &str = " wants you to sign in with your Solana account:";
+
+/// Clock difference tolerated between the wallet and the server.
+const SKEW: i64 = 60;
+
+/// Bytes of a nonce: random bytes, expiry in milliseconds and the start of
+/// their HMAC, so it fits `login_nonces.nonce` once in base58.
+const RANDOM: usize = 16;
+const EXPIRY: usize = 8;
+const TAG: usize = 16;
+
+/// A Sign-In-With-Solana message, as shown by the wallet:
+///
+/// ```text
+/// ai-generated.dev wants you to sign in with your Solana account:
+/// <base58 pubkey>
+///
+/// <optional statement>
+///
+/// URI: https://ai-generated.dev
+/// Version: 1
+/// Chain ID: mainnet
+/// Nonce: <nonce from GET /nonce>
+/// Issued At: 2023-06-27T12:00:00Z
+/// Expiration Time: 2023-06-27T12:05:00Z
+/// ```
+///
+/// `URI`, `Version` and `Chain ID` are optional.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignIn {
+    pub domain: String,
+    pub address: String,
+    pub statement: Option<String>,
+    pub uri: Option<String>,
+    pub version: Option<String>,
+    pub chain_id: Option<String>,
+    pub nonce: String,
+    pub issued_at: DateTime<Utc>,
+    pub expiration_time: DateTime<Utc>,
+}
+
+/// Why a sign in message was turned down.
+#[derive(Debug)]
+pub enum Refusal {
+    Malformed(String),
+    Domain(String),
+    Address,
+    NotYetValid,
+    Expired,
+    Nonce,
+    Database(sqlx::Error),
+}
+
+impl Refusal {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Refusal::Malformed(_) => StatusCode::BAD_REQUEST,
+            Refusal::Domain(_)
+            | Refusal::Address
+            | Refusal::NotYetValid
+            | Refusal::Expired
+            | Refusal::Nonce => StatusCode::UNAUTHORIZED,
+            Refusal::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl std::fmt::Display for Refusal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Refusal::Malformed(reason) => write!(f, "Malformed sign in message; {reason}"),
+            Refusal::Domain(domain) => write!(f, "Sign in message for another domain `{domain}`"),
+            Refusal::Address => write!(f, "Sign in message for another account"),
+            Refusal::NotYetValid => write!(f, "Sign in message issued in the future"),
+            Refusal::Expired => write!(f, "Sign in message expired"),
+            Refusal::Nonce => write!(f, "Unknown, used or expired nonce"),
+            Refusal::Database(err) => write!(f, "Database error: {}", err),
+        }
+    }
+}
+
+impl From<sqlx::Error> for Refusal {
+    fn from(err: sqlx::Error) -> Self {
+        Refusal::Database(err)
+    }
+}
+
+fn malformed(reason: &str) -> Refusal {
+    Refusal::Malformed(reason.to_owned())
+}
+
+fn time(value: &str, field: &str) -> Result<DateTime<Utc>, Refusal> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|_| Refusal::Malformed(format!("`{field}` is not an RFC 3339 time")))
+}
+
+impl std::str::FromStr for SignIn {
+    type Err = Refusal;
+
+    fn from_str(message: &str) -> Result<Self, Self::Err> {
+        let mut lines = message.lines();
+        let domain = lines
+            .next()
+            .and_then(|line| line.strip_suffix(HEADER))
+            .filter(|domain| !domain.is_empty())
+            .ok_or_else(|| malformed("missing header"))?;
+        let address = lines
+            .next()
+            .filter(|address| !address.is_empty())
+            .ok_or_else(|| malformed("missing address"))?;
+
+        let mut statement: Option<String> = None;
+        let (mut uri, mut version, mut chain_id) = (None, None, None);
+        let (mut nonce, mut issued_at, mut expiration_time) = (None, None, None);
+        let mut fields = false;
+        for line in lines.filter(|line| !line.is_empty()) {
+            let (name, value) = line.split_once(": ").unwrap_or((line, ""));
+            let field = match name {
+                "URI" => &mut uri,
+                "Version" => &mut version,
+                "Chain ID" => &mut chain_id,
+                "Nonce" => &mut nonce,
+                "Issued At" => &mut issued_at,
+                "Expiration Time" => &mut expiration_time,
+                _ if !fields && statement.is_none() => {
+                    statement = Some(line.to_owned());
+                    continue;
+                }
+                _ => return Err(Refusal::Malformed(format!("unexpected line `{line}`"))),
+            };
+            if value.is_empty() {
+                return Err(Refusal::Malformed(format!("`{name}` is empty")));
+            }
+            if field.replace(value.to_owned()).is_some() {
+                return Err(Refusal::Malformed(format!("`{name}` given twice")));
+            }
+            fields = true;
+        }
+
+        let nonce = nonce.ok_or_else(|| malformed("missing `Nonce`"))?;
+        let issued_at = issued_at.ok_or_else(|| malformed("missing `Issued At`"))?;
+        let expiration_time =
+            expiration_time.ok_or_else(|| malformed("missing `Expiration Time`"))?;
+
+        Ok(SignIn {
+            domain: domain.to_owned(),
+            address: address.to_owned(),
+            statement,
+            uri,
+            version,
+            chain_id,
+            nonce,
+            issued_at: time(&issued_at, "Issued At")?,
+            expiration_time: time(&expiration_time, "Expiration Time")?,
+        })
+    }
+}
+
+impl SignIn {
+    /// Checks the message was written for one of the allowed origins, for
+    /// `pubkey` and is valid now. The nonce is checked by `consume`.
+    pub fn check(&self, origins: &[String], pubkey: &str) -> Result<(), Refusal> {
+        let allowed = origins.iter().any(|origin| {
+            origin
+                .split_once("://")
+                .map_or(origin.as_str(), |(_, host)| host)
+                == self.domain
+        });
+        if !allowed {
+            return Err(Refusal::Domain(self.domain.clone()));
+        }
+        if self.address != pubkey {
+            return Err(Refusal::Address);
+        }
+
+        let now = Utc::now();
+        if self.issued_at > now + chrono::Duration::seconds(SKEW) {
+            return Err(Refusal::NotYetValid);
+        }
+        if self.expiration_time <= now {
+            return Err(Refusal::Expired);
+        }
+        Ok(())
+    }
+}
+
+fn mac(key: &[u8]) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any size");
+    mac.update(b"siws-nonce");
+    mac
+}
+
+/// Issues a nonce to put in the next sign in message, valid once until it
+/// expires. Nothing is stored: the nonce carries its expiry, signed with
+/// `key`, and is only recorded once `consume`d.
+pub fn issue(key: &[u8], ttl: chrono::Duration) -> (String, DateTime<Utc>) {
+    let expires_at = Utc::now() + ttl;
+    let mut nonce = rand::random::<[u8; RANDOM]>().to_vec();
+    nonce.extend(expires_at.timestamp_millis().to_be_bytes());
+    let mut mac = mac(key);
+    mac.update(&nonce);
+    nonce.extend(&mac.finalize().into_bytes()[..TAG]);
+    (bs58::encode(nonce).into_string(), expires_at)
+}
+
+/// Expiry of a nonce issued with `key`, `None` when it was not.
+fn expiry(key: &[u8], nonce: &str) -> Option<DateTime<Utc>> {
+    let bytes = bs58::decode(nonce).into_vec().ok()?;
+    if bytes.len() != RANDOM + EXPIRY + TAG {
+        return None;
+    }
+    let (signed, tag) = bytes.split_at(RANDOM + EXPIRY);
+    let mut mac = mac(key);
+    mac.update(signed);
+    mac.verify_truncated_left(tag).ok()?;
+    let millis = i64::from_be_bytes(signed[RANDOM..].try_into().ok()?);
+    Utc.timestamp_millis_opt(millis).single()
+}
+
+/// Records the nonce used, fails when it was not issued with `key`, was used
+/// already or expired. Used nonces past their expiry are dropped on the way.
+pub async fn consume(pool: &Pool, key: &[u8], nonce: &str) -> Result<(), Refusal> {
+    let expires_at = expiry(key, nonce)
+        .filter(|expires_at| *expires_at > Utc::now())
+        .ok_or(Refusal::Nonce)?;
+
+    sqlx::query!("DELETE FROM login_nonces WHERE expires_at < NOW()")
+        .execute(pool)
+        .await?;
+    sqlx::query_scalar!(
+        r#"INSERT INTO login_nonces (nonce, expires_at, used_at)
+        VALUES ($1, $2, NOW())
+        ON CONFLICT (nonce) DO NOTHING
+        RETURNING nonce"#,
+        nonce,
+        expires_at,
+    )
+    .fetch_optional(pool)
+    .await?
+    .map(|_| ())
+    .ok_or(Refusal::Nonce)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expiry, issue, Refusal, SignIn};
+    use chrono::{Duration, SecondsFormat, Utc};
+
+    const ADDRESS: &str = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin";
+
+    fn message(domain: &str, fields: &[(&str, String)]) -> String {
+        let mut lines = vec![
+            format!("{domain} wants you to sign in with your Solana account:"),
+            ADDRESS.to_owned(),
+            String::new(),
+            "Sign in to ai-generated.dev".to_owned(),
+            String::new(),
+        ];
+        lines.extend(
+            fields
+                .iter()
+                .map(|(name, value)| format!("{name}: {value}")),
+        );
+        lines.join("\n")
+    }
+
+    fn fields(issued_at: Duration, expires_in: Duration) -> Vec<(&'static str, String)> {
+        let now = Utc::now();
+        vec![
+            ("URI", "https://ai-generated.dev".to_owned()),
+            ("Version", "1".to_owned()),
+            ("Nonce", "3bKn5xJ8qWvNk2Hq".to_owned()),
+            (
+                "Issued At",
+                (now + issued_at).to_rfc3339_opts(SecondsFormat::Secs, true),
+            ),
+            (
+                "Expiration Time",
+                (now + expires_in).to_rfc3339_opts(SecondsFormat::Secs, true),
+            ),
+        ]
+    }
+
+    fn valid() -> String {
+        message(
+            "ai-generated.dev",
+            &fields(Duration::zero(), Duration::minutes(5)),
+        )
+    }
+
+    fn without(field: &str) -> String {
+        let fields = fields(Duration::zero(), Duration::minutes(5))
+            .into_iter()
+            .filter(|(name, _)| *name != field)
+            .collect::<Vec<_>>();
+        message("ai-generated.dev", &fields)
+    }
+
+    fn malformed(message: &str) -> bool {
+        matches!(message.parse::<SignIn>(), Err(Refusal::Malformed(_)))
+    }
+
+    fn origins() -> Vec<String> {
+        vec![
+            "https://ai-generated.dev".to_owned(),
+            "http://localhost:3000".to_owned(),
+        ]
+    }
+
+    #[test]
+    fn parse() {
+        let message = valid();
+        let sign_in = message.parse::<SignIn>().unwrap();
+        assert_eq!(sign_in.domain, "ai-generated.dev");
+        assert_eq!(sign_in.address, ADDRESS);
+        assert_eq!(
+            sign_in.statement.as_deref(),
+            Some("Sign in to ai-generated.dev")
+        );
+        assert_eq!(sign_in.uri.as_deref(), Some("https://ai-generated.dev"));
+        assert_eq!(sign_in.version.as_deref(), Some("1"));
+        assert_eq!(sign_in.chain_id, None);
+        assert_eq!(sign_in.nonce, "3bKn5xJ8qWvNk2Hq");
+        assert!(sign_in.check(&origins(), ADDRESS).is_ok());
+
+        let crlf = message.replace('\n', "\r\n").parse::<SignIn>().unwrap();
+        assert_eq!(crlf, sign_in);
+    }
+
+    #[test]
+    fn missing() {
+        assert!(malformed(&valid().replacen(
+            " wants you to",
+            " would like to",
+            1
+        )));
+        assert!(malformed(""));
+        assert!(malformed(&without("Nonce")));
+        assert!(malformed(&without("Issued At")));
+        assert!(malformed(&without("Expiration Time")));
+        assert!(without("URI").parse::<SignIn>().is_ok());
+    }
+
+    #[test]
+    fn duplicate() {
+        let nonce = format!("{}\nNonce: 7pLm2cR9sTfWy4Dz", valid());
+        assert!(malformed(&nonce));
+        let statement = valid().replacen("\nURI:", "\nSign in to ai-generated.dev again\nURI:", 1);
+        assert!(malformed(&statement));
+    }
+
+    #[test]
+    fn domain() {
+        let other = message("evil.dev", &fields(Duration::zero(), Duration::minutes(5)));
+        let sign_in = other.parse::<SignIn>().unwrap();
+        assert!(matches!(
+            sign_in.check(&origins(), ADDRESS),
+            Err(Refusal::Domain(_))
+        ));
+
+        let fields = fields(Duration::zero(), Duration::minutes(5));
+        let port = message("localhost:3000", &fields)
+            .parse::<SignIn>()
+            .unwrap();
+        assert!(port.check(&origins(), ADDRESS).is_ok());
+        let no_port = message("localhost", &fields).parse::<SignIn>().unwrap();
+        assert!(matches!(
+            no_port.check(&origins(), ADDRESS),
+            Err(Refusal::Domain(_))
+        ));
+        let wrong_port = message("localhost:8080", &fields)
+            .parse::<SignIn>()
+            .unwrap();
+        assert!(matches!(
+            wrong_port.check(&origins(), ADDRESS),
+            Err(Refusal::Domain(_))
+        ));
+    }
+
+    #[test]
+    fn address() {
+        let sign_in = valid().parse::<SignIn>().unwrap();
+        assert!(matches!(
+            sign_in.check(&origins(), "4Nd1mBQtrMJVYVfKf2PJy9NZUZdTAsp7D4xWLs4gDB4T"),
+            Err(Refusal::Address)
+        ));
+    }
+
+    #[test]
+    fn time() {
+        let skewed = message(
+            "ai-generated.dev",
+            &fields(Duration::seconds(30), Duration::minutes(5)),
+        );
+        assert!(skewed
+            .parse::<SignIn>()
+            .unwrap()
+            .check(&origins(), ADDRESS)
+            .is_ok());
+
+        let future = message(
+            "ai-generated.dev",
+            &fields(Duration::minutes(10), Duration::minutes(15)),
+        );
+        assert!(matches!(
+            future.parse::<SignIn>().unwrap().check(&origins(), ADDRESS),
+            Err(Refusal::NotYetValid)
+        ));
+
+        let expired = message(
+            "ai-generated.dev",
+            &fields(Duration::minutes(-10), Duration::minutes(-5)),
+        );
+        assert!(matches!(
+            expired
+                .parse::<SignIn>()
+                .unwrap()
+                .check(&origins(), ADDRESS),
+            Err(Refusal::Expired)
+        ));
+
+        let unreadable = valid().replacen("Issued At: ", "Issued At: yesterday ", 1);
+        assert!(malformed(&unreadable));
+    }
+
+    #[test]
+    fn nonce() {
+        let (nonce, expires_at) = issue(b"secret", Duration::minutes(5));
+        assert!(nonce.len() <= 64);
+        assert_eq!(
+            expiry(b"secret", &nonce).map(|time| time.timestamp_millis()),
+            Some(expires_at.timestamp_millis())
+        );
+        assert_eq!(expiry(b"other secret", &nonce), None);
+        assert_eq!(expiry(b"secret", "3bKn5xJ8qWvNk2Hq"), None);
+
+        let mut bytes = bs58::decode(&nonce).into_vec().unwrap();
+        bytes[20] ^= 1;
+        assert_eq!(expiry(b"secret", &bs58::encode(bytes).into_string()), None);
+    }
+}
@@ -0,0 +1,316 @@
+use axum::http::{header, Response, StatusCode};
+use axum_extra::extract::cookie::{Cookie, SameSite};
+use chrono::TimeZone;
+use jsonwebtoken::{encode, EncodingKey, Header};
+use sha2::{Digest, Sha256};
+
+use crate::db::{
+    model::{RefreshToken, TokenClaims, User},
+    Config,
+};
+
+type Pool = sqlx::Pool<sqlx::Postgres>;
+
+pub const ACCESS_COOKIE: &str = "token";
+pub const REFRESH_COOKIE: &str = "refresh_token";
+
+/// A short lived JWT and the opaque token trading it for a new pair.
+#[derive(Debug, Clone)]
+pub struct Tokens {
+    pub access: String,
+    pub refresh: String,
+}
+
+/// Why a refresh token was not traded.
+#[derive(Debug)]
+pub enum Refusal {
+    Invalid,
+    Expired,
+    /// A rotated token came back: the tokens it was rotated into and every
+    /// access token of its user are revoked. API keys are left alone.
+    Reused,
+    Disabled,
+    Database(sqlx::Error),
+}
+
+impl Refusal {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Refusal::Invalid | Refusal::Expired | Refusal::Reused => StatusCode::UNAUTHORIZED,
+            Refusal::Disabled => StatusCode::FORBIDDEN,
+            Refusal::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl std::fmt::Display for Refusal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Refusal::Invalid => write!(f, "Invalid refresh token"),
+            Refusal::Expired => write!(f, "Refresh token expired, please log in again"),
+            Refusal::Reused => write!(f, "Refresh token reused, please log in again"),
+            Refusal::Disabled => write!(f, "This account is disabled"),
+            Refusal::Database(err) => write!(f, "Database error: {}", err),
+        }
+    }
+}
+
+impl From<sqlx::Error> for Refusal {
+    fn from(err: sqlx::Error) -> Self {
+        Refusal::Database(err)
+    }
+}
+
+/// Only the hash of refresh tokens and API keys is stored.
+pub(crate) fn hash(token: &str) -> String {
+    bs58::encode(Sha256::digest(token.as_bytes())).into_string()
+}
+
+fn access(config: &Config, user_id: uuid::Uuid) -> String {
+    let now = chrono::Utc::now();
+    let claims = TokenClaims {
+        sub: user_id.to_string(),
+        iat: now.timestamp() as usize,
+        exp: (now + config.jwt_expires_in).timestamp() as usize,
+        jti: uuid::Uuid::new_v4().to_string(),
+    };
+
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(config.jwt_secret.as_ref()),
+    )
+    .unwrap()
+}
+
+async fn refresh(
+    pool: &Pool,
+    config: &Config,
+    user_id: uuid::Uuid,
+) -> Result<(uuid::Uuid, String), sqlx::Error> {
+    let token = bs58::encode(rand::random::<[u8; 32]>()).into_string();
+    let id = sqlx::query_scalar!(
+        "INSERT INTO refresh_tokens (user_id, token_hash, expires_at) VALUES ($1, $2, $3) RETURNING id",
+        user_id,
+        hash(&token),
+        chrono::Utc::now() + config.refresh_expires_in,
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok((id, token))
+}
+
+/// Issues a new pair of tokens to the user.
+pub async fn issue(
+    pool: &Pool,
+    config: &Config,
+    user_id: uuid::Uuid,
+) -> Result<Tokens, sqlx::Error> {
+    let (_, refresh) = refresh(pool, config, user_id).await?;
+    Ok(Tokens {
+        access: access(config, user_id),
+        refresh,
+    })
+}
+
+/// Trades a refresh token for a new pair, the traded one can not be used
+/// again.
+pub async fn rotate(pool: &Pool, config: &Config, token: &str) -> Result<Tokens, Refusal> {
+    let token_hash = hash(token);
+    let used = sqlx::query_as!(
+        RefreshToken,
+        r#"UPDATE refresh_tokens SET revoked_at = NOW()
+        WHERE token_hash = $1 AND revoked_at IS NULL AND expires_at > NOW()
+        RETURNING *"#,
+        token_hash,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    let Some(used) = used else {
+        let known = sqlx::query_as!(
+            RefreshToken,
+            "SELECT * FROM refresh_tokens WHERE token_hash = $1",
+            token_hash,
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        return Err(match known {
+            None => Refusal::Invalid,
+            Some(known) if known.revoked_at.is_some() => {
+                revoke_chain(pool, &known).await?;
+                Refusal::Reused
+            }
+            Some(_) => Refusal::Expired,
+        });
+    };
+
+    let user = sqlx::query_as!(User, "SELECT * FROM users WHERE id = $1", used.user_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(Refusal::Invalid)?;
+    if user.disabled {
+        return Err(Refusal::Disabled);
+    }
+
+    let (id, refresh) = refresh(pool, config, user.id).await?;
+    sqlx::query!(
+        "UPDATE refresh_tokens SET replaced_by = $2 WHERE id = $1",
+        used.id,
+        id,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(Tokens {
+        access: access(config, user.id),
+        refresh,
+    })
+}
+
+/// Puts an access token on the revocation list until it expires, and drops
+/// the entries that expired already.
+pub async fn revoke(pool: &Pool, claims: &TokenClaims) -> Result<(), sqlx::Error> {
+    let (Ok(jti), Ok(user_id)) = (
+        uuid::Uuid::parse_str(&claims.jti),
+        uuid::Uuid::parse_str(&claims.sub),
+    ) else {
+        return Ok(());
+    };
+    let expires_at = chrono::Utc
+        .timestamp_opt(claims.exp as i64, 0)
+        .single()
+        .unwrap_or_else(chrono::Utc::now);
+
+    sqlx::query!("DELETE FROM revoked_tokens WHERE expires_at < NOW()")
+        .execute(pool)
+        .await?;
+    sqlx::query!(
+        "INSERT INTO revoked_tokens (jti, user_id, expires_at) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING",
+        jti,
+        user_id,
+        expires_at,
+    )
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
+
+pub async fn revoked(pool: &Pool, claims: &TokenClaims) -> Result<bool, sqlx::Error> {
+    let Ok(jti) = uuid::Uuid::parse_str(&claims.jti) else {
+        return Ok(true);
+    };
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM revoked_tokens WHERE jti = $1) AS "revoked!""#,
+        jti,
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Revokes the refresh tokens `token` was rotated into, one after the other,
+/// and refuses the access tokens of its user issued so far.
+async fn revoke_chain(pool: &Pool, token: &RefreshToken) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"WITH RECURSIVE chain AS (
+            SELECT id, replaced_by FROM refresh_tokens WHERE id = $1
+            UNION
+            SELECT next.id, next.replaced_by FROM refresh_tokens next
+            JOIN chain ON next.id = chain.replaced_by
+        )
+        UPDATE refresh_tokens SET revoked_at = COALESCE(revoked_at, NOW())
+        WHERE id IN (SELECT id FROM chain)"#,
+        token.id,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query!(
+        "UPDATE users SET tokens_revoked_at = NOW(), updated_at = NOW() WHERE id = $1",
+        token.user_id,
+    )
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
+
+pub async fn revoke_refresh(pool: &Pool, token: &str) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE refresh_tokens SET revoked_at = NOW() WHERE token_hash = $1 AND revoked_at IS NULL",
+        hash(token),
+    )
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
+
+/// Revokes every refresh token and API key of the user, access tokens are
+/// refused through `users.tokens_revoked_at`.
+pub async fn revoke_all(pool: &Pool, user_id: uuid::Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE refresh_tokens SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL",
+        user_id,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query!(
+        "UPDATE api_keys SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL",
+        user_id,
+    )
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
+
+fn cookie(name: &str, value: String, max_age: time::Duration, http_only: bool) -> Cookie<'static> {
+    Cookie::build(name.to_owned(), value)
+        .path("/")
+        .max_age(max_age)
+        .secure(true)
+        .same_site(SameSite::None)
+        .http_only(http_only)
+        .finish()
+}
+
+/// Answers with both tokens in the body and in cookies.
+pub fn response(config: &Config, tokens: Tokens) -> Response<String> {
+    let access = cookie(
+        ACCESS_COOKIE,
+        tokens.access.clone(),
+        time::Duration::minutes(config.jwt_maxage.into()),
+        false,
+    );
+    let refresh = cookie(
+        REFRESH_COOKIE,
+        tokens.refresh.clone(),
+        time::Duration::seconds(config.refresh_expires_in.num_seconds()),
+        true,
+    );
+
+    let mut response = Response::new(
+        serde_json::json!({
+            "status": "success",
+            "token": tokens.access,
+            "refresh_token": tokens.refresh,
+        })
+        .to_string(),
+    );
+    for cookie in [access, refresh] {
+        response
+            .headers_mut()
+            .append(header::SET_COOKIE, cookie.to_string().parse().unwrap());
+    }
+    response
+}
+
+/// Answers with both cookies expired.
+pub fn cleared() -> Response<String> {
+    let mut response = Response::new(serde_json::json!({"status": "success"}).to_string());
+    for (name, http_only) in [(ACCESS_COOKIE, false), (REFRESH_COOKIE, true)] {
+        let cookie = cookie(name, String::new(), time::Duration::hours(-1), http_only);
+        response
+            .headers_mut()
+            .append(header::SET_COOKIE, cookie.to_string().parse().unwrap());
+    }
+    response
+}
@@ -4,11 +4,13 @@ use axum::http::header::{
     COOKIE,
 };
 use axum::http::Method;
-use tower_http::cors::CorsLayer;
+use shared::config::Application;
+use tokio::sync::watch;
+use tower_http::cors::{AllowOrigin, CorsLayer};
 
-use shared::constants;
-
-pub fn load() -> CorsLayer {
+/// Origins are checked against the live configuration, so a reload applies
+/// to the next request.
+pub fn load(application: watch::Receiver<Application>) -> CorsLayer {
     CorsLayer::new()
         .allow_methods([Method::GET, Method::POST, Method::PATCH, Method::DELETE])
         .allow_headers([
@@ -21,6 +23,13 @@ pub fn load() -> CorsLayer {
             AUTHORIZATION,
             ACCEPT,
         ])
-        .allow_origin(constants::server::CORS_ALLOWED_ORIGINS.map(|origin| origin.parse().unwrap()))
+        .allow_origin(AllowOrigin::predicate(move |origin, _| {
+            application
+                .borrow()
+                .server
+                .cors_allowed_origins
+                .iter()
+                .any(|allowed| allowed.as_bytes() == origin.as_bytes())
+        }))
         .allow_credentials(true)
 }
@@ -1,13 +1,33 @@
 pub mod model;
 
+use shared::types::Role;
 use std::sync::Arc;
 
 #[derive(Debug, Clone)]
 pub struct Config {
     pub database_url: String,
     pub jwt_secret: String,
-    pub jwt_expires_in: String,
+    /// Lifetime of the access tokens.
+    pub jwt_expires_in: chrono::Duration,
+    /// Max age of the access token cookie, in minutes.
     pub jwt_maxage: i32,
+    /// Lifetime of the refresh tokens and of their cookie.
+    pub refresh_expires_in: chrono::Duration,
+}
+
+/// Reads durations such as `30s`, `15m`, `1h` or `30d`.
+fn duration(value: &str) -> Option<chrono::Duration> {
+    let value = value.trim();
+    let (amount, unit) = value.split_at(value.len() - value.chars().last()?.len_utf8());
+    let amount = amount.parse::<i64>().ok()?;
+    match unit {
+        "s" => Some(chrono::Duration::seconds(amount)),
+        "m" => Some(chrono::Duration::minutes(amount)),
+        "h" => Some(chrono::Duration::hours(amount)),
+        "d" => Some(chrono::Duration::days(amount)),
+        _ => None,
+    }
+    .filter(|duration| *duration > chrono::Duration::zero())
 }
 
 impl Config {
@@ -16,21 +36,24 @@ impl Config {
         let jwt_secret = std::env::var("JWT_SECRET").expect("JWT_SECRET must be set");
         let jwt_expires_in = std::env::var("JWT_EXPIRED_IN").expect("JWT_EXPIRED_IN must be set");
         let jwt_maxage = std::env::var("JWT_MAXAGE").expect("JWT_MAXAGE must be set");
+        let refresh_expires_in =
+            std::env::var("REFRESH_EXPIRED_IN").unwrap_or_else(|_| "30d".to_string());
         Config {
             database_url,
             jwt_secret,
-            jwt_expires_in,
+            jwt_expires_in: duration(&jwt_expires_in)
+                .expect("JWT_EXPIRED_IN must be a duration such as 15m"),
             jwt_maxage: jwt_maxage.parse::<i32>().unwrap(),
+            refresh_expires_in: duration(&refresh_expires_in)
+                .expect("REFRESH_EXPIRED_IN must be a duration such as 30d"),
         }
     }
 }
 
-pub async fn init() -> (Arc<sqlx::Pool<sqlx::Postgres>>, Config) {
-    let config = Config::init();
-
-    let pool = match sqlx::postgres::PgPoolOptions::new()
+async fn connect(database_url: &str) -> sqlx::Pool<sqlx::Postgres> {
+    match sqlx::postgres::PgPoolOptions::new()
         .max_connections(10)
-        .connect(&config.database_url)
+        .connect(database_url)
         .await
     {
         Ok(pool) => {
@@ -41,8 +64,64 @@ pub async fn init() -> (Arc<sqlx::Pool<sqlx::Postgres>>, Config) {
             println!("🔥 Failed to connect to the database: {:?}", err);
             std::process::exit(1);
         }
-    };
-    let arced_pool = Arc::new(pool);
+    }
+}
+
+pub async fn init() -> (Arc<sqlx::Pool<sqlx::Postgres>>, Config) {
+    let config = Config::init();
+    let arced_pool = Arc::new(connect(&config.database_url).await);
 
     (arced_pool, config)
 }
+
+/// Makes the user of `pubkey` a verified and enabled admin, creating it when
+/// it never logged in. Only needs `DATABASE_URL`.
+pub async fn bootstrap_admin(pubkey: &str) -> Result<(), &'static str> {
+    if !bs58::decode(pubkey)
+        .into_vec()
+        .is_ok_and(|key| key.len() == 32)
+    {
+        return Err("Invalid pubkey, expected a base58 ed25519 public key");
+    }
+
+    let database_url = std::env::var("DATABASE_URL").map_err(|_| "DATABASE_URL must be set")?;
+    let pool = connect(&database_url).await;
+
+    let user = sqlx::query_as!(
+        model::User,
+        r#"INSERT INTO users (pubkey, role, verified) VALUES ($1, $2, TRUE)
+        ON CONFLICT (pubkey) DO UPDATE
+            SET role = $2, verified = TRUE, disabled = FALSE, updated_at = NOW()
+        RETURNING *"#,
+        pubkey,
+        Role::Admin.to_string(),
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|err| {
+        println!("🔥 {err:?}");
+        "Failed to bootstrap the admin"
+    })?;
+
+    println!("✅{} is now an admin ({})", user.pubkey, user.id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::duration;
+
+    #[test]
+    fn durations() {
+        assert_eq!(duration("15m"), Some(chrono::Duration::minutes(15)));
+        assert_eq!(duration("30d"), Some(chrono::Duration::days(30)));
+        assert_eq!(duration(" 30s "), Some(chrono::Duration::seconds(30)));
+        assert_eq!(duration("1h"), Some(chrono::Duration::hours(1)));
+        assert_eq!(duration(""), None);
+        assert_eq!(duration("0h"), None);
+        assert_eq!(duration("-1h"), None);
+        assert_eq!(duration("5x"), None);
+        assert_eq!(duration("h"), None);
+        assert_eq!(duration("15"), None);
+    }
+}
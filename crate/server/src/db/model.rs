@@ -1,5 +1,6 @@
 use chrono::prelude::*;
 use serde::{Deserialize, Serialize};
+use shared::types::{Role, Scope};
 
 #[allow(non_snake_case)]
 #[derive(Debug, Deserialize, sqlx::FromRow, Serialize, Clone)]
@@ -13,13 +14,106 @@ pub struct User {
     pub created_at: Option<DateTime<Utc>>,
     #[serde(rename = "updatedAt")]
     pub updated_at: Option<DateTime<Utc>>,
+    pub disabled: bool,
+    /// Tokens issued before this second are refused.
+    #[serde(rename = "tokensRevokedAt")]
+    pub tokens_revoked_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl User {
+    /// Unknown roles are read as the least privileged one.
+    pub fn role(&self) -> Role {
+        self.role.parse().unwrap_or_default()
+    }
+
+    /// Whether a token issued at `iat` was revoked since. `iat` is in whole
+    /// seconds, so a token issued in the second of the revocation is kept,
+    /// such as the one of a sign in right after it.
+    pub fn revoked(&self, iat: usize) -> bool {
+        self.tokens_revoked_at
+            .is_some_and(|revoked_at| (iat as i64) < revoked_at.timestamp())
+    }
+}
+
+#[derive(Debug, Deserialize, sqlx::FromRow, Serialize, Clone)]
+pub struct Job {
+    pub id: uuid::Uuid,
+    #[serde(rename = "userId")]
+    pub user_id: uuid::Uuid,
+    #[serde(rename = "modelType")]
+    pub model_type: String,
+    pub input: serde_json::Value,
+    pub status: String,
+    pub output: Option<serde_json::Value>,
+    pub error: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "startedAt")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(rename = "finishedAt")]
+    pub finished_at: Option<DateTime<Utc>>,
+    #[serde(rename = "durationMs")]
+    pub duration_ms: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, sqlx::FromRow, Serialize, Clone)]
+pub struct ApiKey {
+    pub id: uuid::Uuid,
+    #[serde(rename = "userId")]
+    pub user_id: uuid::Uuid,
+    pub name: String,
+    /// Start of the key, to tell keys apart without storing them.
+    pub prefix: String,
+    #[serde(skip_serializing)]
+    pub key_hash: String,
+    #[serde(rename = "modelTypes")]
+    pub model_types: serde_json::Value,
+    #[serde(rename = "commandTypes")]
+    pub command_types: serde_json::Value,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "lastUsedAt")]
+    pub last_used_at: Option<DateTime<Utc>>,
+    #[serde(rename = "revokedAt")]
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl ApiKey {
+    /// Unreadable lists are read as empty, which allows nothing.
+    pub fn scope(&self) -> Scope {
+        Scope::restricted(
+            serde_json::from_value(self.model_types.clone()).unwrap_or_default(),
+            serde_json::from_value(self.command_types.clone()).unwrap_or_default(),
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TokenClaims {
     pub sub: String,
     pub iat: usize,
     pub exp: usize,
+    /// Id of the token in the revocation list.
+    pub jti: String,
+}
+
+#[derive(Debug, Deserialize, sqlx::FromRow, Serialize, Clone)]
+pub struct RefreshToken {
+    pub id: uuid::Uuid,
+    #[serde(rename = "userId")]
+    pub user_id: uuid::Uuid,
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: DateTime<Utc>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "revokedAt")]
+    pub revoked_at: Option<DateTime<Utc>>,
+    #[serde(rename = "replacedBy")]
+    pub replaced_by: Option<uuid::Uuid>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -31,3 +125,34 @@ pub struct RegisterUserSchema {
 pub struct LoginUserSchema {
     pub pubkey: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::User;
+    use chrono::{TimeZone, Utc};
+
+    fn user(tokens_revoked_at: Option<i64>) -> User {
+        User {
+            id: uuid::Uuid::nil(),
+            pubkey: String::new(),
+            role: "user".to_owned(),
+            photo: String::new(),
+            verified: false,
+            created_at: None,
+            updated_at: None,
+            disabled: false,
+            tokens_revoked_at: tokens_revoked_at
+                .map(|millis| Utc.timestamp_millis_opt(millis).unwrap()),
+        }
+    }
+
+    #[test]
+    fn revoked() {
+        assert!(!user(None).revoked(1_000));
+
+        let user = user(Some(1_000_700));
+        assert!(user.revoked(999));
+        assert!(!user.revoked(1_000));
+        assert!(!user.revoked(1_001));
+    }
+}
@@ -1,46 +1,78 @@
-use axum_server::HttpConfig;
+use axum_server::{Handle, HttpConfig};
 use shared::{
     command::Command,
-    constants::{chan, server::PORT},
+    config::{Application, Flags},
+    constants::time,
     message::Message,
-    types::ModelType,
+    model::Models,
+    types::Preload,
 };
-use std::net::SocketAddr;
-use tokio::sync::{broadcast, mpsc};
+use std::{net::SocketAddr, path::PathBuf, time::Duration};
+use tokio::sync::{broadcast, mpsc, watch};
 
 pub mod app;
 pub mod cors;
 pub mod db;
+pub mod reload;
+pub mod shutdown;
 pub mod tls;
 
 use actors::supervisor;
 
-pub async fn run(ip: Option<String>, port: Option<String>) -> Result<(), &'static str> {
-    console_subscriber::init();
+/// Fails when a preloaded model is missing some of its files in `.ai-data`,
+/// after listing them.
+fn check_ressources(preload: &[Preload]) -> Result<(), &'static str> {
+    let models = Models::load();
+    let missing: Vec<PathBuf> = preload
+        .iter()
+        .filter(|preload| preload.replicas > 0)
+        .flat_map(|preload| models.missing(preload.model_type.ressource()))
+        .collect();
 
-    let (htx, srx) = mpsc::channel::<Command>(chan::MPSC_LEN);
-    let (stx, _unused) = broadcast::channel::<Message>(chan::BORDCAST_LEN);
-    let tx = stx.clone();
+    for path in &missing {
+        println!("missing model ressource: {}", path.display());
+    }
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err("Missing model ressources, fetch them with `airs loader add`")
+    }
+}
 
-    let ip = ip
-        .map(|value| serde_json::from_str(&value).expect("env must be defined"))
-        .unwrap_or([127, 0, 0, 1]);
+pub async fn run(flags: Flags) -> Result<(), &'static str> {
+    console_subscriber::init();
+
+    let application = Application::load(flags.clone()).map_err(|err| {
+        println!("{err}");
+        "Invalid configuration"
+    })?;
+    check_ressources(&application.preload)?;
+    let server = application.server.clone();
 
-    let port = port
-        .map(|value| serde_json::from_str(&value).expect("env must be defined"))
-        .unwrap_or(PORT);
+    let (htx, srx) = mpsc::channel::<Command>(application.channel.mpsc_size);
+    let (stx, _unused) = broadcast::channel::<Message>(application.channel.bordcast_size);
+    let tx = stx.clone();
+    let (settings_tx, settings) = watch::channel(supervisor::Settings::from(&application));
+    let (application_tx, application) = watch::channel(application);
+    reload::watch(flags, application_tx, settings_tx, stx.clone())?;
+    let (pool, config) = db::init().await;
+    let (closing_tx, closing) = watch::channel(false);
+    let handle = Handle::new();
+    let server_handle = handle.clone();
+    let server_pool = pool.clone();
 
     let axum = tokio::task::Builder::new()
         .name("axum server")
         .spawn(async move {
-            let socket = SocketAddr::from((ip, port));
+            let socket = SocketAddr::from((server.address, server.port));
             println!("socket: {socket:?}");
 
-            let router = app::router(htx, tx).await;
+            let router = app::router(htx, tx, server_pool, config, closing, application).await;
             let tls_config = tls::config_load(true).await;
             let http_config = HttpConfig::new().http2_only(true).build();
 
             axum_server::bind_rustls(socket, tls_config)
+                .handle(server_handle)
                 .http_config(http_config)
                 .serve(router.into_make_service())
                 .await
@@ -50,12 +82,21 @@ pub async fn run(ip: Option<String>, port: Option<String>) -> Result<(), &'stati
 
     let supervisor = tokio::task::Builder::new()
         .name("supervisor")
-        .spawn(async move {
-            supervisor::run(stx, srx, vec![ModelType::Sentiment, ModelType::Translation]).await
-        })
+        .spawn(async move { supervisor::run(stx, srx, settings).await })
         .map_err(|_| "Cannot spawn supervisor")?;
 
-    axum.await
-        .and(supervisor.await)
-        .map_err(|_| "Unexpected error")
+    shutdown::signal().await;
+    println!("shutting down");
+
+    // Commands and SSE streams are turned down first, then the server stops
+    // accepting connections; the supervisor winds the workers down once the
+    // last command sender is dropped with the router.
+    closing_tx.send_replace(true);
+    handle.graceful_shutdown(Some(Duration::from_millis(time::SHUTDOWN)));
+
+    let served = axum.await;
+    let supervised = supervisor.await;
+    pool.close().await;
+
+    served.and(supervised).map_err(|_| "Unexpected error")
 }
@@ -0,0 +1,105 @@
+use actors::supervisor::Settings;
+use shared::{
+    config::{path, Application, Flags},
+    constants::time,
+    message::{
+        emit::{self, EmitSource},
+        Message,
+    },
+};
+use std::time::SystemTime;
+use tokio::sync::{broadcast, watch};
+
+fn modified_at() -> Option<SystemTime> {
+    std::fs::metadata(path::application())
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Resolves once the configuration file was written since `modified`.
+async fn file_changed(modified: &mut Option<SystemTime>) {
+    loop {
+        shared::tools::wait(time::RELOAD).await;
+        let current = modified_at();
+        if current != *modified {
+            *modified = current;
+            return;
+        }
+    }
+}
+
+/// Loads the configuration again and publishes it when it is valid and
+/// differs from the running one, then reports the changes with
+/// `ConfigReloaded`. An invalid file leaves the running configuration as is.
+fn reload(
+    flags: &Flags,
+    application: &watch::Sender<Application>,
+    settings: &watch::Sender<Settings>,
+    tx: &broadcast::Sender<Message>,
+) {
+    let next = match Application::load(flags.clone()) {
+        Ok(next) => next,
+        Err(err) => {
+            println!("configuration not reloaded; {err}");
+            return;
+        }
+    };
+    if let Err(err) = crate::check_ressources(&next.preload) {
+        println!("configuration not reloaded; {err}");
+        return;
+    }
+
+    let changes = application.borrow().changes(&next);
+    if changes.is_empty() {
+        return;
+    }
+    for change in changes.iter().filter(|change| !change.live) {
+        println!("`{}` changed, restart to apply it", change.key);
+    }
+
+    // Channels are sized once, new workers keep the size of the first ones.
+    let worker_channel = settings.borrow().worker_channel;
+    settings.send_replace(Settings {
+        worker_channel,
+        ..Settings::from(&next)
+    });
+    application.send_replace(next);
+
+    if let Err(err) = emit::config_reloaded(tx, EmitSource::default(), changes) {
+        println!("{err:#?}");
+    }
+}
+
+/// Reloads `application.ron` whenever it changes on disk or the process gets
+/// SIGHUP. The command line flags keep overriding the file.
+pub fn watch(
+    flags: Flags,
+    application: watch::Sender<Application>,
+    settings: watch::Sender<Settings>,
+    tx: broadcast::Sender<Message>,
+) -> Result<(), &'static str> {
+    #[cfg(unix)]
+    let mut hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+        .map_err(|_| "Cannot listen for SIGHUP")?;
+
+    tokio::task::Builder::new()
+        .name("config reload")
+        .spawn(async move {
+            let mut modified = modified_at();
+            loop {
+                #[cfg(unix)]
+                let hangup = hangup.recv();
+                #[cfg(not(unix))]
+                let hangup = std::future::pending::<Option<()>>();
+
+                tokio::select! {
+                    _ = hangup => (),
+                    _ = file_changed(&mut modified) => (),
+                }
+                reload(&flags, &application, &settings, &tx);
+                modified = modified_at();
+            }
+        })
+        .map(|_| ())
+        .map_err(|_| "Cannot spawn the config reload")
+}
@@ -0,0 +1,35 @@
+use tokio::sync::watch;
+
+/// Resolves on the first SIGINT or SIGTERM.
+pub async fn signal() {
+    let interrupt = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("cannot listen for SIGINT");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("cannot listen for SIGTERM")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = interrupt => (),
+        _ = terminate => (),
+    }
+}
+
+/// Resolves once the server started closing, or when the flag is gone.
+pub async fn closed(mut closing: watch::Receiver<bool>) {
+    while !*closing.borrow() {
+        if closing.changed().await.is_err() {
+            break;
+        }
+    }
+}
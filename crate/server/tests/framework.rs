@@ -1,7 +1,7 @@
 use axum::Router;
 use std::{net::SocketAddr, sync::Arc};
 use tokio::{
-    sync::{self, RwLock},
+    sync::{self, watch, RwLock},
     task::JoinHandle,
 };
 
@@ -11,9 +11,10 @@ use server::{
 };
 
 use actors::supervisor;
-use shared::types::ModelType;
+use shared::types::{ModelType, Preload};
 use shared::{
     command::Command,
+    config::{Application, Flags},
     constants::{server::ADDRESS, server::PORT},
     message::Message,
 };
@@ -24,7 +25,8 @@ pub async fn build_app(
 ) -> Router {
     let supervisor_tx_clone = supervisor_tx;
     let state = State::new(http_tx, supervisor_tx_clone, None, None);
-    let cors = cors::load();
+    let (_reload, application) = watch::channel(Application::load(Flags::default()).unwrap());
+    let cors = cors::load(application);
     app(Arc::new(RwLock::new(state)), cors)
 }
 
@@ -50,12 +52,14 @@ pub async fn run_supervisor(
     let supervisor = tokio::task::Builder::new()
         .name("supervisor")
         .spawn(async move {
-            supervisor::run(
-                supervisor_tx,
-                supervisor_rx,
-                vec![ModelType::Summarize, ModelType::Sentiment],
-            )
-            .await
+            let (_reload, settings) = watch::channel(supervisor::Settings {
+                preload: vec![
+                    Preload::new(ModelType::Summarize),
+                    Preload::new(ModelType::Sentiment),
+                ],
+                ..Default::default()
+            });
+            supervisor::run(supervisor_tx, supervisor_rx, settings).await
         })
         .unwrap();
 
@@ -38,29 +38,18 @@ about exoplanets like K2-18b."];
 pub const TRANSLATION_INPUT: [&str; 1] =
     ["This sentence will be translated in multiple languages."];
 
-#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
-struct Input {
-    input: String,
-}
+struct Input;
 
 impl Input {
-    fn input(model_type: &ModelType) -> String {
+    fn input(model_type: &ModelType) -> serde_json::Value {
         match model_type {
-            ModelType::Sentiment => serde_json::to_string(&Input {
-                input: SENTIMENT_INPUT.to_string(),
-            })
-            .ok()
-            .unwrap(),
-            ModelType::Summarize => serde_json::to_string(&Input {
-                input: serde_json::to_string(&SUMMARZE_INPUT).ok().unwrap(),
-            })
-            .ok()
-            .unwrap(),
-            ModelType::Translation => serde_json::to_string(&Input {
-                input: serde_json::to_string(&TRANSLATION_INPUT).ok().unwrap(),
-            })
-            .ok()
-            .unwrap(),
+            ModelType::Sentiment => json!({ "input": SENTIMENT_INPUT }),
+            ModelType::Summarize => json!({ "input": SUMMARZE_INPUT[0] }),
+            ModelType::Translation => json!({
+                "input": TRANSLATION_INPUT[0],
+                "source_lang": "en",
+                "target_lang": "fr",
+            }),
             _ => unimplemented!(),
         }
     }
@@ -116,7 +105,7 @@ pub async fn post_cmd(
             serde_json::to_vec(&json!(playload::Process::new(
                 model_type,
                 task_id.unwrap(),
-                &Input::input(&model_type)
+                Input::input(&model_type)
             ))),
             route::API_COMMAND_PROCESS_URL,
         ),
@@ -132,6 +121,10 @@ pub async fn post_cmd(
             serde_json::to_vec(&json!(playload::Resume::new(model_type, task_id.unwrap()))),
             route::API_COMMAND_RESUME_URL,
         ),
+        CommandType::Cancel => (
+            serde_json::to_vec(&json!(playload::Cancel::new(model_type, task_id.unwrap()))),
+            route::API_COMMAND_CANCEL_URL,
+        ),
     };
     let body = Body::from(payload.ok().unwrap());
     let req = new_req(cookie, route, body).await;
@@ -0,0 +1,59 @@
+use super::Instruction;
+use crate::schema::ModelInput;
+use crate::types::CommandType;
+use crate::types::ModelType;
+use crate::types::Role;
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Cancel {
+    pub command_type: CommandType,
+    pub model_type: ModelType,
+    pub job_id: String,
+    pub timestamp: u128,
+    pub owner: String,
+    #[serde(default)]
+    pub role: Role,
+}
+
+impl Cancel {
+    pub fn new(owner: &str, model_type: ModelType, job_id: &str) -> Self {
+        Self {
+            command_type: CommandType::Cancel,
+            model_type,
+            job_id: job_id.to_string(),
+            timestamp: crate::tools::time(),
+            owner: owner.to_string(),
+            role: Role::default(),
+        }
+    }
+}
+
+impl Instruction for Cancel {
+    fn command_type(&self) -> CommandType {
+        self.command_type
+    }
+
+    fn model_type(&self) -> ModelType {
+        self.model_type
+    }
+
+    fn task_id(&self) -> Option<String> {
+        None
+    }
+
+    fn input(&self) -> Option<ModelInput> {
+        None
+    }
+
+    fn job_id(&self) -> Option<String> {
+        Some(self.job_id.clone())
+    }
+
+    fn timestamp(&self) -> u128 {
+        self.timestamp
+    }
+
+    fn owner(&self) -> String {
+        self.owner.clone()
+    }
+}
@@ -1,6 +1,8 @@
 use super::Instruction;
+use crate::schema::ModelInput;
 use crate::types::CommandType;
 use crate::types::ModelType;
+use crate::types::Role;
 
 #[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct Kill {
@@ -9,6 +11,8 @@ pub struct Kill {
     pub task_id: String,
     pub timestamp: u128,
     pub owner: String,
+    #[serde(default)]
+    pub role: Role,
 }
 
 impl Kill {
@@ -19,6 +23,7 @@ impl Kill {
             task_id: task_id.to_string(),
             timestamp: crate::tools::time(),
             owner: owner.to_string(),
+            role: Role::default(),
         }
     }
 }
@@ -36,7 +41,11 @@ impl Instruction for Kill {
         Some(self.task_id.clone())
     }
 
-    fn json_input(&self) -> Option<String> {
+    fn input(&self) -> Option<ModelInput> {
+        None
+    }
+
+    fn job_id(&self) -> Option<String> {
         None
     }
 
@@ -13,6 +13,10 @@ pub use resume::Resume;
 pub mod spawn;
 pub use spawn::Spawn;
 
+pub mod cancel;
+pub use cancel::Cancel;
+
+use crate::schema::ModelInput;
 use crate::types::CommandType;
 use crate::types::ModelType;
 
@@ -20,7 +24,8 @@ pub trait Instruction: Send + Sync {
     fn command_type(&self) -> CommandType;
     fn model_type(&self) -> ModelType;
     fn task_id(&self) -> Option<String>;
-    fn json_input(&self) -> Option<String>;
+    fn input(&self) -> Option<ModelInput>;
+    fn job_id(&self) -> Option<String>;
     fn owner(&self) -> String;
     fn timestamp(&self) -> u128;
 }
@@ -1,6 +1,8 @@
 use super::Instruction;
+use crate::schema::ModelInput;
 use crate::types::CommandType;
 use crate::types::ModelType;
+use crate::types::Role;
 
 #[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct Pause {
@@ -9,6 +11,21 @@ pub struct Pause {
     pub task_id: String,
     pub timestamp: u128,
     pub owner: String,
+    #[serde(default)]
+    pub role: Role,
+}
+
+impl Pause {
+    pub fn new(owner: &str, model_type: ModelType, task_id: &str) -> Self {
+        Self {
+            command_type: CommandType::Pause,
+            model_type,
+            task_id: task_id.to_string(),
+            timestamp: crate::tools::time(),
+            owner: owner.to_string(),
+            role: Role::default(),
+        }
+    }
 }
 
 impl Instruction for Pause {
@@ -24,7 +41,11 @@ impl Instruction for Pause {
         Some(self.task_id.clone())
     }
 
-    fn json_input(&self) -> Option<String> {
+    fn input(&self) -> Option<ModelInput> {
+        None
+    }
+
+    fn job_id(&self) -> Option<String> {
         None
     }
 
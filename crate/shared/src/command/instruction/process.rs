@@ -1,15 +1,20 @@
 use super::Instruction;
+use crate::schema::ModelInput;
 use crate::types::CommandType;
 use crate::types::ModelType;
+use crate::types::Role;
 
 #[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct Process {
     pub command_type: CommandType,
     pub model_type: ModelType,
     pub task_id: Option<String>,
-    pub json_input: String,
+    pub job_id: String,
+    pub input: ModelInput,
     pub timestamp: u128,
     pub owner: String,
+    #[serde(default)]
+    pub role: Role,
 }
 
 impl Instruction for Process {
@@ -25,8 +30,12 @@ impl Instruction for Process {
         self.task_id.clone()
     }
 
-    fn json_input(&self) -> Option<String> {
-        Some(self.json_input.clone())
+    fn input(&self) -> Option<ModelInput> {
+        Some(self.input.clone())
+    }
+
+    fn job_id(&self) -> Option<String> {
+        Some(self.job_id.clone())
     }
 
     fn timestamp(&self) -> u128 {
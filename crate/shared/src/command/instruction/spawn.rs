@@ -1,6 +1,9 @@
 use super::Instruction;
+use crate::schema::ModelInput;
 use crate::types::CommandType;
+use crate::types::Device;
 use crate::types::ModelType;
+use crate::types::Role;
 
 #[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct Spawn {
@@ -8,17 +11,28 @@ pub struct Spawn {
     pub model_type: ModelType,
     pub timestamp: u128,
     pub owner: String,
+    #[serde(default)]
+    pub role: Role,
+    #[serde(default)]
+    pub device: Device,
 }
 
 impl Spawn {
+    /// A spawn decided by the supervisor itself, which may spawn any model.
     pub fn new(owner: &str, model_type: ModelType) -> Self {
         Self {
             command_type: CommandType::Spawn,
             model_type,
             timestamp: crate::tools::time(),
             owner: owner.to_string(),
+            role: Role::Admin,
+            device: Device::Auto,
         }
     }
+
+    pub fn with_device(self, device: Device) -> Self {
+        Self { device, ..self }
+    }
 }
 
 impl Instruction for Spawn {
@@ -34,7 +48,11 @@ impl Instruction for Spawn {
         None
     }
 
-    fn json_input(&self) -> Option<String> {
+    fn input(&self) -> Option<ModelInput> {
+        None
+    }
+
+    fn job_id(&self) -> Option<String> {
         None
     }
 
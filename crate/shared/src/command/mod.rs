@@ -1,8 +1,11 @@
 pub mod instruction;
 
+use crate::schema::ModelInput;
 use crate::tools::root;
 use crate::types::CommandType;
+use crate::types::Device;
 use crate::types::ModelType;
+use crate::types::Role;
 
 use self::instruction::Instruction;
 
@@ -13,8 +16,10 @@ pub enum Command {
     Pause(instruction::Pause),
     Resume(instruction::Resume),
     Spawn(instruction::Spawn),
+    Cancel(instruction::Cancel),
 }
 
+/// Commands built here are sent on behalf of `ROOT`, with the admin role.
 impl Command {
     pub fn boxed_instruction(&self) -> Box<dyn Instruction> {
         match self {
@@ -23,26 +28,57 @@ impl Command {
             Command::Pause(instruction) => Box::new(instruction.clone()),
             Command::Resume(instruction) => Box::new(instruction.clone()),
             Command::Spawn(instruction) => Box::new(instruction.clone()),
+            Command::Cancel(instruction) => Box::new(instruction.clone()),
         }
     }
 
+    pub fn job_id(&self) -> Option<String> {
+        self.boxed_instruction().job_id()
+    }
+
     pub fn spawn(model_type: ModelType) -> Self {
         Command::Spawn(instruction::Spawn {
             command_type: CommandType::Spawn,
             timestamp: crate::tools::time(),
             model_type,
             owner: crate::constants::role::ROOT.to_owned(),
+            role: Role::Admin,
+            device: Device::Auto,
         })
     }
 
-    pub fn process(id: &str, input: &str, model_type: ModelType) -> Self {
+    /// Spawns a worker on the given device rather than letting the supervisor place it.
+    pub fn spawn_on(model_type: ModelType, device: Device) -> Self {
+        Command::Spawn(instruction::Spawn {
+            role: Role::Admin,
+            ..instruction::Spawn::new(crate::constants::role::ROOT, model_type).with_device(device)
+        })
+    }
+
+    pub fn process(id: &str, input: ModelInput) -> Self {
         Command::Process(instruction::Process {
             command_type: CommandType::Process,
             timestamp: crate::tools::time(),
-            model_type,
+            model_type: input.model_type(),
             owner: root(),
+            role: Role::Admin,
             task_id: Some(id.to_owned()),
-            json_input: input.to_owned(),
+            job_id: crate::tools::job_id(),
+            input,
+        })
+    }
+
+    /// Lets the supervisor pick the worker of the input model type to run it on.
+    pub fn dispatch(input: ModelInput) -> Self {
+        Command::Process(instruction::Process {
+            command_type: CommandType::Process,
+            timestamp: crate::tools::time(),
+            model_type: input.model_type(),
+            owner: root(),
+            role: Role::Admin,
+            task_id: None,
+            job_id: crate::tools::job_id(),
+            input,
         })
     }
 
@@ -52,7 +88,41 @@ impl Command {
             timestamp: crate::tools::time(),
             model_type,
             owner: root(),
+            role: Role::Admin,
+            task_id: id.to_owned(),
+        })
+    }
+
+    pub fn pause(id: &str, model_type: ModelType) -> Self {
+        Command::Pause(instruction::Pause {
+            command_type: CommandType::Pause,
+            timestamp: crate::tools::time(),
+            model_type,
+            owner: root(),
+            role: Role::Admin,
             task_id: id.to_owned(),
         })
     }
+
+    pub fn resume(id: &str, model_type: ModelType) -> Self {
+        Command::Resume(instruction::Resume {
+            command_type: CommandType::Resume,
+            timestamp: crate::tools::time(),
+            model_type,
+            owner: root(),
+            role: Role::Admin,
+            task_id: id.to_owned(),
+        })
+    }
+
+    pub fn cancel(job_id: &str, model_type: ModelType) -> Self {
+        Command::Cancel(instruction::Cancel {
+            command_type: CommandType::Cancel,
+            timestamp: crate::tools::time(),
+            model_type,
+            owner: root(),
+            role: Role::Admin,
+            job_id: job_id.to_owned(),
+        })
+    }
 }
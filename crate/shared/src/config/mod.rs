@@ -1,39 +1,227 @@
 pub mod path;
+pub mod reload;
 
+pub use reload::ConfigChange;
+
+use crate::types::{Batching, Preload, Residency};
+
+/// Prefix of the environment variables overriding `application.ron`, e.g.
+/// `AIRS_SERVER__PORT=8443` or `AIRS_SERVER__CORS_ALLOWED_ORIGINS=https://a,https://b`.
+pub const ENV_PREFIX: &str = "AIRS";
+
+/// Reads `configuration/application.ron`, then the `AIRS_` environment
+/// variables on top of it.
 pub fn get_config<'a, T: serde::Deserialize<'a>>() -> Result<Box<T>, configure::ConfigError> {
-    let configuration_path = path::workspace()
-        .join("configuration")
-        .join("application.ron");
+    let configuration_path = path::application();
+    let environment = configure::Environment::with_prefix(ENV_PREFIX)
+        .prefix_separator("_")
+        .separator("__")
+        .list_separator(",")
+        .with_list_parse_key("server.cors_allowed_origins")
+        .try_parsing(true);
 
     let settings = configure::Config::builder()
         .add_source(configure::File::from(configuration_path))
+        .add_source(environment)
         .build()?;
 
     settings.try_deserialize::<Box<T>>()
 }
 
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    /// The file or the environment cannot be read into the configuration.
+    Load(String),
+    /// A value was read but cannot be used.
+    Invalid { key: &'static str, reason: String },
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Load(reason) => write!(f, "cannot load the configuration; {reason}"),
+            ConfigError::Invalid { key, reason } => write!(f, "invalid `{key}`; {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Values given on the command line, they win over the file and the
+/// environment. Empty lists leave the configured ones untouched.
+#[derive(Debug, Clone, Default)]
+pub struct Flags {
+    pub port: Option<u16>,
+    pub address: Option<[u8; 4]>,
+    pub cors_allowed_origins: Vec<String>,
+    pub preload: Vec<Preload>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Application {
     pub server: Server,
     pub channel: Channel,
+    pub preload: Vec<Preload>,
+    pub batching: Batching,
+    pub residency: Residency,
+    pub diffusion: Diffusion,
+    pub limits: Limits,
     pub version: u64,
 }
 
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Server {
-    port: u16,
-    address: [u8; 4],
-    cors_allowed_origins: [String; 5],
+    pub port: u16,
+    pub address: [u8; 4],
+    pub cors_allowed_origins: Vec<String>,
+    /// How long the `/api/v1` endpoints wait for a prediction, in ms.
+    pub inference_timeout: u64,
 }
 
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Channel {
-    mpsc_size: usize,
-    bordcast_size: usize,
+    pub mpsc_size: usize,
+    pub bordcast_size: usize,
+    pub queue_size: usize,
+    /// Events kept per owner to replay to a reconnecting SSE client.
+    pub replay_size: usize,
+}
+
+/// Values used for the diffusion inputs a request leaves out.
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+pub struct Diffusion {
+    pub height: i64,
+    pub width: i64,
+    pub seed: i64,
+}
+
+impl Diffusion {
+    /// Fills the fields missing from a diffusion input object.
+    pub fn fill(&self, input: &mut serde_json::Value) {
+        if let serde_json::Value::Object(map) = input {
+            for (key, value) in [
+                ("height", self.height),
+                ("width", self.width),
+                ("seed", self.seed),
+            ] {
+                map.entry(key).or_insert(value.into());
+            }
+        }
+    }
+}
+
+/// Largest inputs a request may ask for.
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+pub struct Limits {
+    pub diffusion_height: i64,
+    pub diffusion_width: i64,
+    pub llama_sample_len: usize,
+}
+
+impl Application {
+    pub fn load(flags: Flags) -> Result<Self, ConfigError> {
+        let mut application =
+            *get_config::<Application>().map_err(|err| ConfigError::Load(err.to_string()))?;
+        application.apply(flags);
+        application.validate()?;
+        Ok(application)
+    }
+
+    fn apply(&mut self, flags: Flags) {
+        let Flags {
+            port,
+            address,
+            cors_allowed_origins,
+            preload,
+        } = flags;
+
+        self.server.port = port.unwrap_or(self.server.port);
+        self.server.address = address.unwrap_or(self.server.address);
+        if !cors_allowed_origins.is_empty() {
+            self.server.cors_allowed_origins = cors_allowed_origins;
+        }
+        if !preload.is_empty() {
+            self.preload = preload;
+        }
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |key, reason: &str| {
+            Err(ConfigError::Invalid {
+                key,
+                reason: reason.to_owned(),
+            })
+        };
+
+        if self.server.port == 0 {
+            return invalid("server.port", "must not be 0");
+        }
+        if self.server.inference_timeout == 0 {
+            return invalid("server.inference_timeout", "must be positive");
+        }
+        if let Some(origin) = self
+            .server
+            .cors_allowed_origins
+            .iter()
+            .find(|origin| !origin.starts_with("https://") && !origin.starts_with("http://"))
+        {
+            return invalid(
+                "server.cors_allowed_origins",
+                &format!("`{origin}` is not an http(s) origin"),
+            );
+        }
+
+        let Channel {
+            mpsc_size,
+            bordcast_size,
+            queue_size,
+            replay_size,
+        } = self.channel;
+        for (key, size) in [
+            ("channel.mpsc_size", mpsc_size),
+            ("channel.bordcast_size", bordcast_size),
+            ("channel.queue_size", queue_size),
+            ("channel.replay_size", replay_size),
+        ] {
+            if size == 0 {
+                return invalid(key, "must be positive");
+            }
+        }
+
+        for (key, size) in [
+            ("diffusion.height", self.diffusion.height),
+            ("diffusion.width", self.diffusion.width),
+        ] {
+            if size <= 0 || size % 8 != 0 {
+                return invalid(key, "must be a positive multiple of 8");
+            }
+        }
+        for (key, size, limit) in [
+            (
+                "diffusion.height",
+                self.diffusion.height,
+                self.limits.diffusion_height,
+            ),
+            (
+                "diffusion.width",
+                self.diffusion.width,
+                self.limits.diffusion_width,
+            ),
+        ] {
+            if size > limit {
+                return invalid(key, "must not exceed its limit");
+            }
+        }
+        if self.limits.llama_sample_len == 0 {
+            return invalid("limits.llama_sample_len", "must be positive");
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::{Application, ConfigError, Flags};
 
     #[test]
     fn workspace_path() {
@@ -52,4 +240,63 @@ mod tests {
         let path = super::path::data();
         println!("{path:?}");
     }
+
+    #[test]
+    fn flags_override() {
+        let application = Application::load(Flags {
+            port: Some(9443),
+            cors_allowed_origins: vec!["https://localhost:3443".to_owned()],
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(application.server.port, 9443);
+        assert_eq!(
+            application.server.cors_allowed_origins,
+            ["https://localhost:3443"]
+        );
+    }
+
+    #[test]
+    fn diffusion_defaults() {
+        let defaults = super::Diffusion {
+            height: 512,
+            width: 768,
+            seed: 42,
+        };
+        let mut input = serde_json::json!({ "prompt": "robot", "height": 256 });
+        defaults.fill(&mut input);
+        assert_eq!(
+            input,
+            serde_json::json!({ "prompt": "robot", "height": 256, "width": 768, "seed": 42 })
+        );
+    }
+
+    #[test]
+    fn invalid_values() {
+        let error = Application::load(Flags {
+            port: Some(0),
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            ConfigError::Invalid {
+                key: "server.port",
+                ..
+            }
+        ));
+
+        let error = Application::load(Flags {
+            cors_allowed_origins: vec!["localhost".to_owned()],
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            ConfigError::Invalid {
+                key: "server.cors_allowed_origins",
+                ..
+            }
+        ));
+    }
 }
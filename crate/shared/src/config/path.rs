@@ -13,6 +13,10 @@ pub fn config() -> PathBuf {
     workspace().join("configuration")
 }
 
+pub fn application() -> PathBuf {
+    config().join("application.ron")
+}
+
 pub mod model {
     pub const DISTILBERT_SST2: &str = "distilbert_sst2";
     pub const DISTILBART_CNN_6_6: &str = "distilbart_cnn_6_6";
@@ -0,0 +1,93 @@
+use serde_json::Value;
+
+use super::Application;
+
+/// Keys read once at startup, a new value only applies after a restart.
+pub const RESTART_REQUIRED: [&str; 5] = [
+    "server.port",
+    "server.address",
+    "channel.mpsc_size",
+    "channel.bordcast_size",
+    "channel.replay_size",
+];
+
+/// A configuration value changed by a reload.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct ConfigChange {
+    pub key: String,
+    pub before: Value,
+    pub after: Value,
+    /// `false` when the new value waits for a restart.
+    pub live: bool,
+}
+
+fn walk(key: &str, before: &Value, after: &Value, changes: &mut Vec<ConfigChange>) {
+    match (before, after) {
+        (Value::Object(before), Value::Object(after)) => {
+            for (name, value) in before {
+                let key = if key.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{key}.{name}")
+                };
+                walk(
+                    &key,
+                    value,
+                    after.get(name).unwrap_or(&Value::Null),
+                    changes,
+                );
+            }
+        }
+        (before, after) if before != after => changes.push(ConfigChange {
+            key: key.to_owned(),
+            before: before.clone(),
+            after: after.clone(),
+            live: !RESTART_REQUIRED.contains(&key),
+        }),
+        _ => (),
+    }
+}
+
+impl Application {
+    /// Values differing in `next`, lists are compared as a whole.
+    pub fn changes(&self, next: &Application) -> Vec<ConfigChange> {
+        let mut changes = Vec::new();
+        if let (Ok(before), Ok(after)) = (serde_json::to_value(self), serde_json::to_value(next)) {
+            walk("", &before, &after, &mut changes);
+        }
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Application, Flags};
+
+    #[test]
+    fn changes() {
+        let application = Application::load(Flags::default()).unwrap();
+        assert!(application.changes(&application).is_empty());
+
+        let mut next = application.clone();
+        next.server.port += 1;
+        next.channel.queue_size += 1;
+        next.server
+            .cors_allowed_origins
+            .push("https://localhost:3443".to_owned());
+
+        let changes = application.changes(&next);
+        let keys: Vec<(&str, bool)> = changes
+            .iter()
+            .map(|change| (change.key.as_str(), change.live))
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                ("channel.queue_size", true),
+                ("server.cors_allowed_origins", true),
+                ("server.port", false),
+            ]
+        );
+        assert_eq!(changes[0].after, next.channel.queue_size);
+    }
+}
@@ -7,38 +7,63 @@ pub mod role {
 pub mod chan {
     pub const MPSC_LEN: usize = 200;
     pub const BORDCAST_LEN: usize = 200;
+    pub const QUEUE_LEN: usize = 16;
 }
 
 pub mod time {
     pub const VOLATILE: u64 = 25;
     pub const FOREVER: u64 = u64::MAX;
     pub const INTERVAL: u64 = 2_000;
+    pub const WATCHDOG: u64 = 250;
+    pub const SHUTDOWN: u64 = 30_000;
+    pub const SHUTDOWN_CANCEL: u64 = 5_000;
+    pub const RELOAD: u64 = 1_000;
+    pub const KEEPALIVE: u64 = 15_000;
+    pub const NONCE_TTL: u64 = 300_000;
+    pub const REPLAY_IDLE: u64 = 3_600_000;
+}
+
+pub mod batch {
+    pub const MAX_SIZE: usize = 8;
+    pub const MAX_WAIT: u64 = 20;
+}
+
+pub mod restart {
+    pub const MAX_RESTARTS: u32 = 5;
+    pub const BACKOFF: u64 = 500;
+    pub const MAX_BACKOFF: u64 = 30_000;
 }
 
 pub mod route {
     pub const HEALTH_URL: &str = "/health";
     pub const SSE_URL: &str = "/sse";
+    pub const WS_URL: &str = "/ws";
     pub const API_COMMAND_PROCESS_URL: &str = "/command/process";
     pub const API_COMMAND_KILL_URL: &str = "/command/kill";
     pub const API_COMMAND_PAUSE_URL: &str = "/command/pause";
     pub const API_COMMAND_RESUME_URL: &str = "/command/resume";
     pub const API_COMMAND_SPAWN_URL: &str = "/command/spawn";
+    pub const API_COMMAND_CANCEL_URL: &str = "/command/cancel";
+    pub const API_WORKERS_URL: &str = "/workers";
+    pub const API_JOBS_URL: &str = "/jobs";
+    pub const API_JOB_URL: &str = "/jobs/:id";
+    pub const API_V1_URL: &str = "/v1/:model";
+    pub const API_KEYS_URL: &str = "/keys";
+    pub const API_KEY_URL: &str = "/keys/:id";
+    pub const API_ADMIN_USERS_URL: &str = "/admin/users";
+    pub const API_ADMIN_USER_ROLE_URL: &str = "/admin/users/:id/role";
+    pub const API_ADMIN_USER_VERIFIED_URL: &str = "/admin/users/:id/verified";
+    pub const API_ADMIN_USER_DISABLED_URL: &str = "/admin/users/:id/disabled";
+    pub const API_ADMIN_USER_REVOKE_URL: &str = "/admin/users/:id/revoke";
     pub const ROOT_URL: &str = "/";
 }
 
+pub mod page {
+    pub const LIMIT: i64 = 20;
+    pub const MAX_LIMIT: i64 = 100;
+}
+
 pub mod server {
     pub const PORT: u16 = 7443;
     pub const ADDRESS: [u8; 4] = [0, 0, 0, 0];
-    pub const CORS_ALLOWED_ORIGINS: [&str; 10] = [
-        "https://dev.ai-generated.dev",
-        "https://www.ai-generated.dev",
-        "https://ai-generated.dev",
-        "https://www.ai-generated.fr",
-        "https://ai-generated.fr",
-        "https://www.ai-generated.fr:8080",
-        "https://ai-generated.fr:8080",
-        "https://localhost:8443",
-        "https://localhost:3443",
-        "http://localhost:3000",
-    ];
 }
@@ -0,0 +1,196 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::watch;
+
+use crate::constants;
+
+type JobIds = Arc<Mutex<HashSet<String>>>;
+
+#[derive(Debug)]
+pub struct Control {
+    tx: watch::Sender<bool>,
+    cancelled: JobIds,
+    pending: JobIds,
+}
+
+/// Worker side of a `Control`; scoped to a job with `with_job_id` before
+/// being handed to a model.
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    rx: watch::Receiver<bool>,
+    cancelled: JobIds,
+    pending: JobIds,
+    job_id: Option<String>,
+}
+
+impl Control {
+    pub fn new() -> (Control, Checkpoint) {
+        let (tx, rx) = watch::channel(false);
+        let cancelled: JobIds = Default::default();
+        let pending: JobIds = Default::default();
+        (
+            Control {
+                tx,
+                cancelled: cancelled.clone(),
+                pending: pending.clone(),
+            },
+            Checkpoint {
+                rx,
+                cancelled,
+                pending,
+                job_id: None,
+            },
+        )
+    }
+
+    pub fn pause(&self) {
+        self.tx.send_replace(true);
+    }
+
+    pub fn resume(&self) {
+        self.tx.send_replace(false);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.tx.borrow()
+    }
+
+    pub fn cancel(&self, job_id: &str) {
+        self.cancelled.lock().unwrap().insert(job_id.to_owned());
+    }
+
+    /// Records a job handed to the worker, until the worker `release`s it.
+    pub fn queue(&self, job_id: &str) {
+        self.pending.lock().unwrap().insert(job_id.to_owned());
+    }
+
+    /// Forgets a job the worker never received.
+    pub fn unqueue(&self, job_id: &str) {
+        self.pending.lock().unwrap().remove(job_id);
+    }
+
+    /// Whether the worker still has to report the end of the job.
+    pub fn is_pending(&self, job_id: &str) -> bool {
+        self.pending.lock().unwrap().contains(job_id)
+    }
+}
+
+impl Checkpoint {
+    pub fn with_job_id(&self, job_id: Option<String>) -> Checkpoint {
+        Checkpoint {
+            job_id,
+            ..self.clone()
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.rx.borrow()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        match &self.job_id {
+            Some(job_id) => self.cancelled.lock().unwrap().contains(job_id),
+            None => false,
+        }
+    }
+
+    /// Forgets the scoped job and its cancellation once the worker reported
+    /// its end.
+    pub fn release(&self) {
+        if let Some(job_id) = &self.job_id {
+            self.cancelled.lock().unwrap().remove(job_id);
+            self.pending.lock().unwrap().remove(job_id);
+        }
+    }
+
+    /// Blocks the calling thread while paused, meant to be called between
+    /// two prediction steps of a synchronous model, off the async runtime:
+    /// workers run predictions on the blocking pool. Returns `false` when the
+    /// scoped job has been cancelled and the model should stop there.
+    pub fn block(&self) -> bool {
+        while self.is_paused() && !self.is_cancelled() {
+            std::thread::sleep(Duration::from_millis(constants::time::VOLATILE));
+        }
+        !self.is_cancelled()
+    }
+
+    /// Waits until the worker is resumed, or until its `Control` is dropped.
+    pub async fn resumed(&mut self) {
+        while self.is_paused() {
+            if self.rx.changed().await.is_err() {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Control;
+
+    #[test]
+    fn pause_and_resume() {
+        let (control, checkpoint) = Control::new();
+        assert!(!checkpoint.is_paused());
+
+        control.pause();
+        assert!(control.is_paused());
+        assert!(checkpoint.is_paused());
+
+        control.resume();
+        assert!(!checkpoint.is_paused());
+        assert!(checkpoint.block());
+    }
+
+    #[test]
+    fn cancel_job() {
+        let (control, checkpoint) = Control::new();
+        let first = checkpoint.with_job_id(Some("first".to_owned()));
+        let second = checkpoint.with_job_id(Some("second".to_owned()));
+
+        control.pause();
+        control.cancel("first");
+        assert!(first.is_cancelled());
+        assert!(!second.is_cancelled());
+        assert!(!checkpoint.is_cancelled());
+        assert!(!first.block());
+
+        first.release();
+        assert!(!first.is_cancelled());
+    }
+
+    #[test]
+    fn pending_job() {
+        let (control, checkpoint) = Control::new();
+        let job = checkpoint.with_job_id(Some("job".to_owned()));
+
+        control.queue("job");
+        control.queue("other");
+        assert!(control.is_pending("job"));
+
+        job.release();
+        assert!(!control.is_pending("job"));
+        assert!(control.is_pending("other"));
+
+        control.unqueue("other");
+        assert!(!control.is_pending("other"));
+    }
+
+    #[tokio::test]
+    async fn resumed_wakes_up() {
+        let (control, mut checkpoint) = Control::new();
+        control.pause();
+
+        let handle = tokio::spawn(async move {
+            checkpoint.resumed().await;
+            checkpoint.is_paused()
+        });
+        crate::tools::wait(crate::constants::time::VOLATILE).await;
+        control.resume();
+
+        assert!(!handle.await.unwrap());
+    }
+}
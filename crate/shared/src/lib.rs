@@ -1,7 +1,9 @@
 pub mod command;
 pub mod config;
 pub mod constants;
+pub mod control;
 pub mod message;
 pub mod model;
+pub mod schema;
 pub mod tools;
 pub mod types;
@@ -6,22 +6,28 @@ use tokio::{
 
 use crate::{
     command::instruction::Instruction,
+    config::ConfigChange,
     constants,
     message::{
         CommandFailedT, CommandSucessT, HealthT, Message, MessageType, ModelErrorT, ModelKilledT,
         ModelLoadedT, ModelPausedT, ModelPredictionT, ModelResumedT, ModelStartedT,
     },
+    schema::ModelOutput,
     tools::root,
-    types::CommandType,
+    types::{CommandType, HealthReport, ModelType},
 };
 
-use super::{LlamaTokenGenT, SchedulerStepT};
+use super::{
+    ConfigReloadedT, JobCancelledT, JobDoneT, JobFailedT, JobQueuedT, JobRunningT, LlamaTokenGenT,
+    ModelCrashedT, ModelEvictedT, QueueFullT, QueuePositionT, SchedulerStepT,
+};
 
 #[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct EmitSource {
     command_type: Option<CommandType>,
     owner: String,
     task_id: Option<String>,
+    job_id: Option<String>,
     model_type: Option<crate::types::model::ModelType>,
 }
 
@@ -31,6 +37,7 @@ impl Default for EmitSource {
             command_type: None,
             owner: root(),
             task_id: None,
+            job_id: None,
             model_type: None,
         }
     }
@@ -42,9 +49,10 @@ impl std::fmt::Display for EmitSource {
             command_type,
             owner,
             task_id,
+            job_id,
             model_type,
         } = self;
-        write!(f, "EmitSource {{ command_type: {command_type:?}, owner: {owner}, task_id: {task_id:?}, model_type: {model_type:?} }}")
+        write!(f, "EmitSource {{ command_type: {command_type:?}, owner: {owner}, task_id: {task_id:?}, job_id: {job_id:?}, model_type: {model_type:?} }}")
     }
 }
 
@@ -55,6 +63,7 @@ impl EmitSource {
         let EmitSource {
             command_type,
             owner,
+            job_id,
             model_type,
             ..
         } = self;
@@ -62,6 +71,7 @@ impl EmitSource {
             command_type: *command_type,
             owner: owner.clone(),
             task_id: Some(task_id.to_string()),
+            job_id: job_id.clone(),
             model_type: *model_type,
         }
     }
@@ -70,6 +80,7 @@ impl EmitSource {
         let EmitSource {
             command_type,
             task_id,
+            job_id,
             model_type,
             ..
         } = self;
@@ -77,15 +88,54 @@ impl EmitSource {
             command_type: *command_type,
             owner: owner.to_owned(),
             task_id: task_id.as_deref().map(|value| value.to_owned()),
+            job_id: job_id.clone(),
+            model_type: *model_type,
+        }
+    }
+
+    pub fn set_job_id(&self, job_id: Option<String>) -> EmitSource {
+        let EmitSource {
+            command_type,
+            owner,
+            task_id,
+            model_type,
+            ..
+        } = self;
+        EmitSource {
+            command_type: *command_type,
+            owner: owner.clone(),
+            task_id: task_id.clone(),
+            job_id,
             model_type: *model_type,
         }
     }
 
+    pub fn job_id(&self) -> Option<String> {
+        self.job_id.clone()
+    }
+
+    pub fn task_id(&self) -> Option<String> {
+        self.task_id.clone()
+    }
+
+    pub fn owner(&self) -> String {
+        self.owner.clone()
+    }
+
+    pub fn model_type(&self) -> Option<ModelType> {
+        self.model_type
+    }
+
+    pub fn command_type(&self) -> Option<CommandType> {
+        self.command_type
+    }
+
     pub fn health(task_id: Id) -> Self {
         Self {
             command_type: None,
             owner: constants::role::ROOT.to_owned(),
             task_id: Some(task_id.to_string()),
+            job_id: None,
             model_type: None,
         }
     }
@@ -97,62 +147,116 @@ impl From<Box<dyn Instruction>> for EmitSource {
             command_type: Some(payload.command_type()),
             owner: payload.owner(),
             task_id: payload.task_id(),
+            job_id: payload.job_id(),
             model_type: Some(payload.model_type()),
         }
     }
 }
 
+/// Pairs the broadcast sender with the source of the job being processed,
+/// so that models can report their progress without knowing about workers.
+#[derive(Debug, Clone)]
+pub struct Emitter {
+    tx: broadcast::Sender<Message>,
+    source: EmitSource,
+}
+
+impl Emitter {
+    pub fn new(tx: broadcast::Sender<Message>, source: EmitSource) -> Self {
+        Self { tx, source }
+    }
+
+    /// The job the emitted messages are about.
+    pub fn job_id(&self) -> Option<String> {
+        self.source.job_id()
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub fn emit(&self, message_type: MessageType, value: Option<&str>) -> Result<usize, EmitError> {
+        message_type.emit(&self.tx, self.source.clone(), value)
+    }
+}
+
+/// Why a message could not be emitted.
+#[derive(Debug)]
+pub enum EmitError {
+    /// Nobody listens on the bus anymore.
+    Closed(SendError<Message>),
+    /// The message carries a typed value, it is sent with its own function
+    /// of this module rather than through `Emit`.
+    Typed(MessageType),
+}
+
+impl std::fmt::Display for EmitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EmitError::Closed(err) => write!(f, "{err}"),
+            EmitError::Typed(message_type) => {
+                write!(f, "{message_type:?} is sent with its typed function")
+            }
+        }
+    }
+}
+
+impl Error for EmitError {}
+
+impl From<SendError<Message>> for EmitError {
+    fn from(err: SendError<Message>) -> Self {
+        EmitError::Closed(err)
+    }
+}
+
+#[allow(clippy::result_large_err)]
 pub trait Emit {
     fn emit(
         &self,
         tx: &broadcast::Sender<Message>,
         source: EmitSource,
         value: Option<&str>,
-    ) -> Result<usize, SendError<Message>>;
+    ) -> Result<usize, EmitError>;
 }
 
+#[allow(clippy::result_large_err)]
 impl Emit for MessageType {
     fn emit(
         &self,
         tx: &broadcast::Sender<Message>,
         source: EmitSource,
         value: Option<&str>,
-    ) -> Result<usize, SendError<Message>> {
+    ) -> Result<usize, EmitError> {
         let message_type = self.clone();
         let message = match self {
-            MessageType::Health => Message::Health(HealthT {
-                timestamp: crate::tools::time(),
-                task_id: source.task_id.unwrap(),
-                owner: source.owner,
-                message_type,
-                value: value.unwrap().to_owned(),
-            }),
             MessageType::SchedulerStep => Message::SchedulerStep(SchedulerStepT {
                 owner: source.owner,
                 timestamp: crate::tools::time(),
+                job_id: source.job_id,
                 command_type: CommandType::Process,
-                model_type: crate::types::ModelType::Diffusion,
+                model_type: source.model_type.unwrap_or(ModelType::Diffusion),
                 message_type,
                 value: value.unwrap().to_owned(),
             }),
             MessageType::LlamaTokenGen => Message::LlamaTokenGen(LlamaTokenGenT {
                 owner: source.owner,
                 timestamp: crate::tools::time(),
+                job_id: source.job_id,
                 command_type: CommandType::Process,
-                model_type: crate::types::ModelType::Diffusion,
+                model_type: source.model_type.unwrap_or(ModelType::Llama),
                 message_type,
                 value: value.unwrap().to_owned(),
             }),
             MessageType::CommandFailed => Message::CommandFailed(CommandFailedT {
                 timestamp: crate::tools::time(),
+                job_id: source.job_id,
                 command_type: source.command_type.unwrap(),
                 owner: source.owner,
                 error: value.unwrap().to_owned(),
                 model_type: source.model_type.unwrap(),
                 message_type,
+                task_id: source.task_id,
             }),
             MessageType::CommandSucess => Message::CommandSucess(CommandSucessT {
                 timestamp: crate::tools::time(),
+                job_id: source.job_id,
                 command_type: source.command_type.unwrap(),
                 model_type: source.model_type.unwrap(),
                 task_id: source.task_id.unwrap(),
@@ -161,6 +265,7 @@ impl Emit for MessageType {
             }),
             MessageType::ModelKilled => Message::ModelKilled(ModelKilledT {
                 timestamp: crate::tools::time(),
+                job_id: source.job_id,
                 model_type: source.model_type.unwrap(),
                 task_id: source.task_id.unwrap(),
                 owner: source.owner,
@@ -168,6 +273,7 @@ impl Emit for MessageType {
             }),
             MessageType::ModelPaused => Message::ModelPaused(ModelPausedT {
                 timestamp: crate::tools::time(),
+                job_id: source.job_id,
                 model_type: source.model_type.unwrap(),
                 task_id: source.task_id.unwrap(),
                 owner: source.owner,
@@ -175,6 +281,7 @@ impl Emit for MessageType {
             }),
             MessageType::ModelResumed => Message::ModelResumed(ModelResumedT {
                 timestamp: crate::tools::time(),
+                job_id: source.job_id,
                 model_type: source.model_type.unwrap(),
                 task_id: source.task_id.unwrap(),
                 owner: source.owner,
@@ -182,6 +289,7 @@ impl Emit for MessageType {
             }),
             MessageType::ModelStarted => Message::ModelStarted(ModelStartedT {
                 timestamp: crate::tools::time(),
+                job_id: source.job_id,
                 model_type: source.model_type.unwrap(),
                 task_id: source.task_id.unwrap(),
                 owner: source.owner,
@@ -189,29 +297,197 @@ impl Emit for MessageType {
             }),
             MessageType::ModelLoaded => Message::ModelLoaded(ModelLoadedT {
                 timestamp: crate::tools::time(),
+                job_id: source.job_id,
+                model_type: source.model_type.unwrap(),
+                task_id: source.task_id.unwrap(),
+                device: value.map(|value| value.to_owned()),
+                memory: None,
+                owner: source.owner,
+                message_type,
+            }),
+            MessageType::ModelError => Message::ModelError(ModelErrorT {
+                timestamp: crate::tools::time(),
+                job_id: source.job_id,
                 model_type: source.model_type.unwrap(),
                 task_id: source.task_id.unwrap(),
                 owner: source.owner,
+                error: value.unwrap().to_owned(),
                 message_type,
             }),
-            MessageType::ModelPrediction => Message::ModelPrediction(ModelPredictionT {
+            MessageType::JobQueued => Message::JobQueued(JobQueuedT {
                 timestamp: crate::tools::time(),
+                job_id: source.job_id.unwrap(),
                 model_type: source.model_type.unwrap(),
                 task_id: source.task_id.unwrap(),
                 owner: source.owner,
-                value: value.unwrap().to_owned(),
                 message_type,
             }),
-            MessageType::ModelError => Message::ModelError(ModelErrorT {
+            MessageType::JobRunning => Message::JobRunning(JobRunningT {
+                timestamp: crate::tools::time(),
+                job_id: source.job_id.unwrap(),
+                model_type: source.model_type.unwrap(),
+                task_id: source.task_id.unwrap(),
+                owner: source.owner,
+                message_type,
+            }),
+            MessageType::JobDone => Message::JobDone(JobDoneT {
+                timestamp: crate::tools::time(),
+                job_id: source.job_id.unwrap(),
+                model_type: source.model_type.unwrap(),
+                task_id: source.task_id.unwrap(),
+                owner: source.owner,
+                message_type,
+            }),
+            MessageType::JobFailed => Message::JobFailed(JobFailedT {
+                timestamp: crate::tools::time(),
+                job_id: source.job_id.unwrap(),
+                model_type: source.model_type.unwrap(),
+                task_id: source.task_id.unwrap(),
+                owner: source.owner,
+                error: value.unwrap().to_owned(),
+                message_type,
+            }),
+            MessageType::JobCancelled => Message::JobCancelled(JobCancelledT {
                 timestamp: crate::tools::time(),
+                job_id: source.job_id.unwrap(),
                 model_type: source.model_type.unwrap(),
                 task_id: source.task_id.unwrap(),
                 owner: source.owner,
+                message_type,
+            }),
+            MessageType::Health
+            | MessageType::ConfigReloaded
+            | MessageType::ModelPrediction
+            | MessageType::QueueFull
+            | MessageType::QueuePosition => return Err(EmitError::Typed(message_type)),
+            MessageType::ModelCrashed => Message::ModelCrashed(ModelCrashedT {
+                timestamp: crate::tools::time(),
+                job_id: source.job_id,
+                model_type: source.model_type.unwrap(),
+                task_id: source.task_id.unwrap(),
                 error: value.unwrap().to_owned(),
+                owner: source.owner,
+                message_type,
+            }),
+            MessageType::ModelEvicted => Message::ModelEvicted(ModelEvictedT {
+                timestamp: crate::tools::time(),
+                job_id: source.job_id,
+                model_type: source.model_type.unwrap(),
+                task_id: source.task_id.unwrap(),
+                reason: value.unwrap().to_owned(),
+                owner: source.owner,
                 message_type,
             }),
         };
 
-        tx.send(message)
+        Ok(tx.send(message)?)
     }
 }
+
+/// Sends a prediction as the worker made it.
+#[allow(clippy::result_large_err)]
+pub fn prediction(
+    tx: &broadcast::Sender<Message>,
+    source: EmitSource,
+    output: ModelOutput,
+) -> Result<usize, SendError<Message>> {
+    tx.send(Message::ModelPrediction(ModelPredictionT {
+        timestamp: crate::tools::time(),
+        job_id: source.job_id,
+        model_type: source.model_type.unwrap(),
+        task_id: source.task_id.unwrap(),
+        owner: source.owner,
+        output,
+        message_type: MessageType::ModelPrediction,
+    }))
+}
+
+/// Sends that a worker loaded its model, with the bytes the process grew by
+/// meanwhile. Going through `Emit` only takes the device.
+#[allow(clippy::result_large_err)]
+pub fn loaded(
+    tx: &broadcast::Sender<Message>,
+    source: EmitSource,
+    device: String,
+    memory: Option<u64>,
+) -> Result<usize, SendError<Message>> {
+    tx.send(Message::ModelLoaded(ModelLoadedT {
+        timestamp: crate::tools::time(),
+        job_id: source.job_id,
+        model_type: source.model_type.unwrap(),
+        task_id: source.task_id.unwrap(),
+        device: Some(device),
+        memory,
+        owner: source.owner,
+        message_type: MessageType::ModelLoaded,
+    }))
+}
+
+/// Sends the health of the workers.
+#[allow(clippy::result_large_err)]
+pub fn health(
+    tx: &broadcast::Sender<Message>,
+    source: EmitSource,
+    report: HealthReport,
+) -> Result<usize, SendError<Message>> {
+    tx.send(Message::Health(HealthT {
+        timestamp: crate::tools::time(),
+        job_id: source.job_id,
+        task_id: source.task_id.unwrap(),
+        owner: source.owner,
+        message_type: MessageType::Health,
+        report,
+    }))
+}
+
+/// Sends the settings a reload changed.
+#[allow(clippy::result_large_err)]
+pub fn config_reloaded(
+    tx: &broadcast::Sender<Message>,
+    source: EmitSource,
+    changes: Vec<ConfigChange>,
+) -> Result<usize, SendError<Message>> {
+    tx.send(Message::ConfigReloaded(ConfigReloadedT {
+        timestamp: crate::tools::time(),
+        job_id: source.job_id,
+        changes,
+        owner: source.owner,
+        message_type: MessageType::ConfigReloaded,
+    }))
+}
+
+/// Sends that the worker of the job has `capacity` jobs queued already.
+#[allow(clippy::result_large_err)]
+pub fn queue_full(
+    tx: &broadcast::Sender<Message>,
+    source: EmitSource,
+    capacity: usize,
+) -> Result<usize, SendError<Message>> {
+    tx.send(Message::QueueFull(QueueFullT {
+        timestamp: crate::tools::time(),
+        job_id: source.job_id.unwrap(),
+        model_type: source.model_type.unwrap(),
+        task_id: source.task_id.unwrap(),
+        capacity,
+        owner: source.owner,
+        message_type: MessageType::QueueFull,
+    }))
+}
+
+/// Sends the rank of a queued job on its worker.
+#[allow(clippy::result_large_err)]
+pub fn queue_position(
+    tx: &broadcast::Sender<Message>,
+    source: EmitSource,
+    position: usize,
+) -> Result<usize, SendError<Message>> {
+    tx.send(Message::QueuePosition(QueuePositionT {
+        timestamp: crate::tools::time(),
+        job_id: source.job_id.unwrap(),
+        model_type: source.model_type.unwrap(),
+        task_id: source.task_id.unwrap(),
+        position,
+        owner: source.owner,
+        message_type: MessageType::QueuePosition,
+    }))
+}
@@ -1,6 +1,11 @@
 pub mod emit;
 
+use std::borrow::Cow;
+
+use crate::config::ConfigChange;
+use crate::schema::ModelOutput;
 use crate::types::CommandType;
+use crate::types::HealthReport;
 use crate::types::MessageType;
 
 #[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
@@ -17,6 +22,16 @@ pub enum Message {
     ModelError(ModelErrorT),
     SchedulerStep(SchedulerStepT),
     LlamaTokenGen(LlamaTokenGenT),
+    JobQueued(JobQueuedT),
+    JobRunning(JobRunningT),
+    JobDone(JobDoneT),
+    JobFailed(JobFailedT),
+    JobCancelled(JobCancelledT),
+    QueueFull(QueueFullT),
+    QueuePosition(QueuePositionT),
+    ModelCrashed(ModelCrashedT),
+    ModelEvicted(ModelEvictedT),
+    ConfigReloaded(ConfigReloadedT),
 }
 
 impl Message {
@@ -24,6 +39,19 @@ impl Message {
         matches!(self, Message::Health(_))
     }
 
+    /// The message as shown to `owner` when not an admin, see
+    /// `HealthReport::redacted`.
+    pub fn redacted(&self, owner: &str) -> Cow<'_, Message> {
+        match self {
+            Message::Health(health) => {
+                let mut health = health.clone();
+                health.report = health.report.redacted(owner);
+                Cow::Owned(Message::Health(health))
+            }
+            _ => Cow::Borrowed(self),
+        }
+    }
+
     pub fn message_type(&self) -> MessageType {
         match self {
             Message::Health(_) => MessageType::Health,
@@ -38,6 +66,16 @@ impl Message {
             Message::ModelError(_) => MessageType::ModelError,
             Message::SchedulerStep(_) => MessageType::SchedulerStep,
             Message::LlamaTokenGen(_) => MessageType::LlamaTokenGen,
+            Message::JobQueued(_) => MessageType::JobQueued,
+            Message::JobRunning(_) => MessageType::JobRunning,
+            Message::JobDone(_) => MessageType::JobDone,
+            Message::JobFailed(_) => MessageType::JobFailed,
+            Message::JobCancelled(_) => MessageType::JobCancelled,
+            Message::QueueFull(_) => MessageType::QueueFull,
+            Message::QueuePosition(_) => MessageType::QueuePosition,
+            Message::ModelCrashed(_) => MessageType::ModelCrashed,
+            Message::ModelEvicted(_) => MessageType::ModelEvicted,
+            Message::ConfigReloaded(_) => MessageType::ConfigReloaded,
         }
     }
 
@@ -51,9 +89,75 @@ impl Message {
             Message::ModelStarted(data) => Some(data.task_id.to_string()),
             Message::Health(data) => Some(data.task_id.to_string()),
             Message::ModelError(data) => Some(data.task_id.to_string()),
+            Message::JobQueued(data) => Some(data.task_id.to_string()),
+            Message::JobRunning(data) => Some(data.task_id.to_string()),
+            Message::JobDone(data) => Some(data.task_id.to_string()),
+            Message::JobFailed(data) => Some(data.task_id.to_string()),
+            Message::JobCancelled(data) => Some(data.task_id.to_string()),
+            Message::QueueFull(data) => Some(data.task_id.to_string()),
+            Message::QueuePosition(data) => Some(data.task_id.to_string()),
+            Message::ModelCrashed(data) => Some(data.task_id.to_string()),
+            Message::ModelEvicted(data) => Some(data.task_id.to_string()),
             _ => None,
         }
     }
+
+    pub fn model_type(&self) -> Option<ModelType> {
+        match self {
+            Message::CommandSucess(data) => Some(data.model_type),
+            Message::CommandFailed(data) => Some(data.model_type),
+            Message::ModelPaused(data) => Some(data.model_type),
+            Message::ModelResumed(data) => Some(data.model_type),
+            Message::ModelKilled(data) => Some(data.model_type),
+            Message::ModelStarted(data) => Some(data.model_type),
+            Message::ModelLoaded(data) => Some(data.model_type),
+            Message::ModelPrediction(data) => Some(data.model_type),
+            Message::ModelError(data) => Some(data.model_type),
+            Message::SchedulerStep(data) => Some(data.model_type),
+            Message::LlamaTokenGen(data) => Some(data.model_type),
+            Message::JobQueued(data) => Some(data.model_type),
+            Message::JobRunning(data) => Some(data.model_type),
+            Message::JobDone(data) => Some(data.model_type),
+            Message::JobFailed(data) => Some(data.model_type),
+            Message::JobCancelled(data) => Some(data.model_type),
+            Message::QueueFull(data) => Some(data.model_type),
+            Message::QueuePosition(data) => Some(data.model_type),
+            Message::ModelCrashed(data) => Some(data.model_type),
+            Message::ModelEvicted(data) => Some(data.model_type),
+            Message::Health(_) | Message::ConfigReloaded(_) => None,
+        }
+    }
+
+    pub fn job_status(&self) -> Option<crate::types::JobStatus> {
+        self.message_type().job_status()
+    }
+
+    pub fn job_id(&self) -> Option<String> {
+        match self {
+            Message::Health(data) => data.job_id.clone(),
+            Message::CommandSucess(data) => data.job_id.clone(),
+            Message::CommandFailed(data) => data.job_id.clone(),
+            Message::ModelPaused(data) => data.job_id.clone(),
+            Message::ModelResumed(data) => data.job_id.clone(),
+            Message::ModelKilled(data) => data.job_id.clone(),
+            Message::ModelStarted(data) => data.job_id.clone(),
+            Message::ModelLoaded(data) => data.job_id.clone(),
+            Message::ModelPrediction(data) => data.job_id.clone(),
+            Message::ModelError(data) => data.job_id.clone(),
+            Message::SchedulerStep(data) => data.job_id.clone(),
+            Message::LlamaTokenGen(data) => data.job_id.clone(),
+            Message::JobQueued(data) => Some(data.job_id.clone()),
+            Message::JobRunning(data) => Some(data.job_id.clone()),
+            Message::JobDone(data) => Some(data.job_id.clone()),
+            Message::JobFailed(data) => Some(data.job_id.clone()),
+            Message::JobCancelled(data) => Some(data.job_id.clone()),
+            Message::QueueFull(data) => Some(data.job_id.clone()),
+            Message::QueuePosition(data) => Some(data.job_id.clone()),
+            Message::ModelCrashed(data) => data.job_id.clone(),
+            Message::ModelEvicted(data) => data.job_id.clone(),
+            Message::ConfigReloaded(data) => data.job_id.clone(),
+        }
+    }
 }
 
 pub trait Owner {
@@ -75,6 +179,16 @@ impl Owner for Message {
             Message::ModelError(data) => &data.owner,
             Message::SchedulerStep(data) => &data.owner,
             Message::LlamaTokenGen(data) => &data.owner,
+            Message::JobQueued(data) => &data.owner,
+            Message::JobRunning(data) => &data.owner,
+            Message::JobDone(data) => &data.owner,
+            Message::JobFailed(data) => &data.owner,
+            Message::JobCancelled(data) => &data.owner,
+            Message::QueueFull(data) => &data.owner,
+            Message::QueuePosition(data) => &data.owner,
+            Message::ModelCrashed(data) => &data.owner,
+            Message::ModelEvicted(data) => &data.owner,
+            Message::ConfigReloaded(data) => &data.owner,
         }
     }
 }
@@ -84,8 +198,9 @@ pub struct HealthT {
     pub owner: String,
     pub message_type: MessageType,
     pub timestamp: u128,
+    pub job_id: Option<String>,
     pub task_id: String,
-    pub value: String,
+    pub report: HealthReport,
 }
 
 use crate::types::ModelType;
@@ -97,6 +212,7 @@ pub struct CommandSucessT {
     pub command_type: CommandType,
     pub model_type: ModelType,
     pub timestamp: u128,
+    pub job_id: Option<String>,
     pub task_id: String,
 }
 
@@ -104,16 +220,19 @@ pub struct CommandSucessT {
 pub struct CommandFailedT {
     pub owner: String,
     pub timestamp: u128,
+    pub job_id: Option<String>,
     pub command_type: CommandType,
     pub model_type: ModelType,
     pub message_type: MessageType,
     pub error: String,
+    pub task_id: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct SchedulerStepT {
     pub owner: String,
     pub timestamp: u128,
+    pub job_id: Option<String>,
     pub command_type: CommandType,
     pub model_type: ModelType,
     pub message_type: MessageType,
@@ -124,6 +243,7 @@ pub struct SchedulerStepT {
 pub struct LlamaTokenGenT {
     pub owner: String,
     pub timestamp: u128,
+    pub job_id: Option<String>,
     pub command_type: CommandType,
     pub model_type: ModelType,
     pub message_type: MessageType,
@@ -134,6 +254,7 @@ pub struct LlamaTokenGenT {
 pub struct ModelPausedT {
     pub owner: String,
     pub timestamp: u128,
+    pub job_id: Option<String>,
     pub message_type: MessageType,
     pub model_type: ModelType,
     pub task_id: String,
@@ -143,6 +264,7 @@ pub struct ModelPausedT {
 pub struct ModelStartedT {
     pub owner: String,
     pub timestamp: u128,
+    pub job_id: Option<String>,
     pub message_type: MessageType,
     pub model_type: ModelType,
     pub task_id: String,
@@ -152,6 +274,7 @@ pub struct ModelStartedT {
 pub struct ModelKilledT {
     pub owner: String,
     pub timestamp: u128,
+    pub job_id: Option<String>,
     pub message_type: MessageType,
     pub model_type: ModelType,
     pub task_id: String,
@@ -161,6 +284,7 @@ pub struct ModelKilledT {
 pub struct ModelResumedT {
     pub owner: String,
     pub timestamp: u128,
+    pub job_id: Option<String>,
     pub message_type: MessageType,
     pub model_type: ModelType,
     pub task_id: String,
@@ -170,9 +294,10 @@ pub struct ModelResumedT {
 pub struct ModelPredictionT {
     pub owner: String,
     pub timestamp: u128,
+    pub job_id: Option<String>,
     pub message_type: MessageType,
     pub model_type: ModelType,
-    pub value: String,
+    pub output: ModelOutput,
     pub task_id: String,
 }
 
@@ -180,17 +305,129 @@ pub struct ModelPredictionT {
 pub struct ModelLoadedT {
     pub owner: String,
     pub timestamp: u128,
+    pub job_id: Option<String>,
     pub message_type: MessageType,
     pub model_type: ModelType,
     pub task_id: String,
+    pub device: Option<String>,
+    pub memory: Option<u64>,
 }
 
 #[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct ModelErrorT {
     pub owner: String,
     pub timestamp: u128,
+    pub job_id: Option<String>,
     pub message_type: MessageType,
     pub model_type: ModelType,
     pub error: String,
     pub task_id: String,
 }
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct JobQueuedT {
+    pub owner: String,
+    pub timestamp: u128,
+    pub job_id: String,
+    pub message_type: MessageType,
+    pub model_type: ModelType,
+    pub task_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct JobRunningT {
+    pub owner: String,
+    pub timestamp: u128,
+    pub job_id: String,
+    pub message_type: MessageType,
+    pub model_type: ModelType,
+    pub task_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct JobDoneT {
+    pub owner: String,
+    pub timestamp: u128,
+    pub job_id: String,
+    pub message_type: MessageType,
+    pub model_type: ModelType,
+    pub task_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct JobFailedT {
+    pub owner: String,
+    pub timestamp: u128,
+    pub job_id: String,
+    pub message_type: MessageType,
+    pub model_type: ModelType,
+    pub error: String,
+    pub task_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct JobCancelledT {
+    pub owner: String,
+    pub timestamp: u128,
+    pub job_id: String,
+    pub message_type: MessageType,
+    pub model_type: ModelType,
+    pub task_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct QueueFullT {
+    pub owner: String,
+    pub timestamp: u128,
+    pub job_id: String,
+    pub message_type: MessageType,
+    pub model_type: ModelType,
+    pub task_id: String,
+    pub capacity: usize,
+}
+
+/// Rank of a queued job on its worker, `1` being the next one to run.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct QueuePositionT {
+    pub owner: String,
+    pub timestamp: u128,
+    pub job_id: String,
+    pub message_type: MessageType,
+    pub model_type: ModelType,
+    pub task_id: String,
+    pub position: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct ModelCrashedT {
+    pub owner: String,
+    pub timestamp: u128,
+    pub job_id: Option<String>,
+    pub message_type: MessageType,
+    pub model_type: ModelType,
+    pub task_id: String,
+    pub error: String,
+}
+
+/// Sent when the supervisor unloads an idle worker; the next job for its
+/// model type pays the cold start again.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct ModelEvictedT {
+    pub owner: String,
+    pub timestamp: u128,
+    pub job_id: Option<String>,
+    pub message_type: MessageType,
+    pub model_type: ModelType,
+    pub task_id: String,
+    pub reason: String,
+}
+
+/// The configuration file was reloaded, with the values that changed.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct ConfigReloadedT {
+    pub owner: String,
+    pub timestamp: u128,
+    pub job_id: Option<String>,
+    pub message_type: MessageType,
+    pub changes: Vec<ConfigChange>,
+}
@@ -1,4 +1,5 @@
 use crate::config::path;
+use std::path::PathBuf;
 
 #[derive(Debug, serde::Deserialize, PartialEq, Eq, Clone)]
 pub struct Models {
@@ -39,6 +40,21 @@ impl Models {
             > 0
     }
 
+    /// Files of the model that are not in `.ai-data` yet, the model folder
+    /// itself when the model is unknown.
+    pub fn missing(&self, model_name: &str) -> Vec<PathBuf> {
+        let folder = path::data().join(model_name);
+        match self.models.iter().find(|model| model.name == model_name) {
+            Some(model) => model
+                .ressources
+                .iter()
+                .map(|ressource| folder.join(&ressource.name))
+                .filter(|path| !path.exists())
+                .collect(),
+            None => vec![folder],
+        }
+    }
+
     pub fn load() -> Models {
         ron::from_str(
             &std::fs::read_to_string(path::config().join("ressources.ron"))
@@ -0,0 +1,124 @@
+use super::SchemaError;
+use crate::{config::Limits, types::ModelType};
+
+/// Languages the translation model can read from and write to.
+pub const LANGUAGES: [&str; 5] = ["en", "fr", "da", "de", "no"];
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct SentimentInput {
+    pub input: String,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct SummarizeInput {
+    pub input: String,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct TranslationInput {
+    pub input: String,
+    pub source_lang: String,
+    pub target_lang: String,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct DiffusionInput {
+    pub prompt: String,
+    pub seed: i64,
+    pub height: i64,
+    pub width: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct LlamaInput {
+    pub prompt: String,
+    pub sample_len: usize,
+    pub temperature: f64,
+}
+
+fn invalid(model_type: ModelType, reason: &str) -> SchemaError {
+    SchemaError::Invalid {
+        model_type,
+        reason: reason.to_owned(),
+    }
+}
+
+fn not_empty(model_type: ModelType, field: &str, value: &str) -> Result<(), SchemaError> {
+    if value.trim().is_empty() {
+        return Err(invalid(model_type, &format!("`{field}` is empty")));
+    }
+    Ok(())
+}
+
+impl SentimentInput {
+    pub fn validate(&self) -> Result<(), SchemaError> {
+        not_empty(ModelType::Sentiment, "input", &self.input)
+    }
+}
+
+impl SummarizeInput {
+    pub fn validate(&self) -> Result<(), SchemaError> {
+        not_empty(ModelType::Summarize, "input", &self.input)
+    }
+}
+
+impl TranslationInput {
+    pub fn validate(&self) -> Result<(), SchemaError> {
+        let model_type = ModelType::Translation;
+        not_empty(model_type, "input", &self.input)?;
+        for lang in [&self.source_lang, &self.target_lang] {
+            if !LANGUAGES.contains(&lang.as_str()) {
+                return Err(invalid(
+                    model_type,
+                    &format!("unsupported language `{lang}`"),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl DiffusionInput {
+    pub fn validate(&self, limits: &Limits) -> Result<(), SchemaError> {
+        let model_type = ModelType::Diffusion;
+        not_empty(model_type, "prompt", &self.prompt)?;
+        for size in [self.height, self.width] {
+            if size <= 0 || size % 8 != 0 {
+                return Err(invalid(
+                    model_type,
+                    "`height` and `width` must be positive multiples of 8",
+                ));
+            }
+        }
+        if self.height > limits.diffusion_height || self.width > limits.diffusion_width {
+            return Err(invalid(
+                model_type,
+                &format!(
+                    "`height` and `width` must not exceed {}x{}",
+                    limits.diffusion_height, limits.diffusion_width
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl LlamaInput {
+    pub fn validate(&self, limits: &Limits) -> Result<(), SchemaError> {
+        let model_type = ModelType::Llama;
+        not_empty(model_type, "prompt", &self.prompt)?;
+        if self.sample_len == 0 || self.sample_len > limits.llama_sample_len {
+            return Err(invalid(
+                model_type,
+                &format!(
+                    "`sample_len` must be between 1 and {}",
+                    limits.llama_sample_len
+                ),
+            ));
+        }
+        if self.temperature.is_nan() || self.temperature <= 0.0 {
+            return Err(invalid(model_type, "`temperature` must be positive"));
+        }
+        Ok(())
+    }
+}
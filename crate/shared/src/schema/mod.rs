@@ -0,0 +1,230 @@
+pub mod input;
+pub mod output;
+
+pub use input::{DiffusionInput, LlamaInput, SentimentInput, SummarizeInput, TranslationInput};
+pub use output::{
+    DiffusionOutput, LlamaOutput, SentimentOutput, SummarizeOutput, TranslationOutput,
+};
+
+use crate::{config::Limits, types::ModelType};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaError {
+    /// The input does not have the shape expected by the model.
+    Malformed {
+        model_type: ModelType,
+        reason: String,
+    },
+    /// The input has the right shape but cannot be run.
+    Invalid {
+        model_type: ModelType,
+        reason: String,
+    },
+    /// The input was built for another model.
+    Mismatch {
+        expected: ModelType,
+        found: ModelType,
+    },
+}
+
+impl std::fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaError::Malformed { model_type, reason } => {
+                write!(f, "malformed {model_type:?} input; {reason}")
+            }
+            SchemaError::Invalid { model_type, reason } => {
+                write!(f, "invalid {model_type:?} input; {reason}")
+            }
+            SchemaError::Mismatch { expected, found } => {
+                write!(f, "expected a {expected:?} input, found a {found:?} one")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum ModelInput {
+    Sentiment(SentimentInput),
+    Summarize(SummarizeInput),
+    Translation(TranslationInput),
+    Diffusion(DiffusionInput),
+    Llama(LlamaInput),
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum ModelOutput {
+    Sentiment(SentimentOutput),
+    Summarize(SummarizeOutput),
+    Translation(TranslationOutput),
+    Diffusion(DiffusionOutput),
+    Llama(LlamaOutput),
+}
+
+impl ModelInput {
+    /// Reads and validates the untyped input sent for the given model.
+    pub fn parse(
+        model_type: ModelType,
+        value: serde_json::Value,
+        limits: &Limits,
+    ) -> Result<Self, SchemaError> {
+        fn typed<T: serde::de::DeserializeOwned>(
+            model_type: ModelType,
+            value: serde_json::Value,
+        ) -> Result<T, SchemaError> {
+            serde_json::from_value(value).map_err(|err| SchemaError::Malformed {
+                model_type,
+                reason: err.to_string(),
+            })
+        }
+
+        let input = match model_type {
+            ModelType::Sentiment => ModelInput::Sentiment(typed(model_type, value)?),
+            ModelType::Summarize => ModelInput::Summarize(typed(model_type, value)?),
+            ModelType::Translation => ModelInput::Translation(typed(model_type, value)?),
+            ModelType::Diffusion => ModelInput::Diffusion(typed(model_type, value)?),
+            ModelType::Llama => ModelInput::Llama(typed(model_type, value)?),
+        };
+        input.validate(limits)?;
+        Ok(input)
+    }
+
+    pub fn model_type(&self) -> ModelType {
+        match self {
+            ModelInput::Sentiment(_) => ModelType::Sentiment,
+            ModelInput::Summarize(_) => ModelType::Summarize,
+            ModelInput::Translation(_) => ModelType::Translation,
+            ModelInput::Diffusion(_) => ModelType::Diffusion,
+            ModelInput::Llama(_) => ModelType::Llama,
+        }
+    }
+
+    /// Checks the input can be run, within the configured `limits`.
+    pub fn validate(&self, limits: &Limits) -> Result<(), SchemaError> {
+        match self {
+            ModelInput::Sentiment(input) => input.validate(),
+            ModelInput::Summarize(input) => input.validate(),
+            ModelInput::Translation(input) => input.validate(),
+            ModelInput::Diffusion(input) => input.validate(limits),
+            ModelInput::Llama(input) => input.validate(limits),
+        }
+    }
+}
+
+macro_rules! schema {
+    ($variant:ident, $input:ident, $output:ident) => {
+        impl From<$input> for ModelInput {
+            fn from(value: $input) -> Self {
+                ModelInput::$variant(value)
+            }
+        }
+
+        impl TryFrom<ModelInput> for $input {
+            type Error = SchemaError;
+
+            fn try_from(value: ModelInput) -> Result<Self, Self::Error> {
+                match value {
+                    ModelInput::$variant(input) => Ok(input),
+                    other => Err(SchemaError::Mismatch {
+                        expected: ModelType::$variant,
+                        found: other.model_type(),
+                    }),
+                }
+            }
+        }
+
+        impl From<$output> for ModelOutput {
+            fn from(value: $output) -> Self {
+                ModelOutput::$variant(value)
+            }
+        }
+    };
+}
+
+schema!(Sentiment, SentimentInput, SentimentOutput);
+schema!(Summarize, SummarizeInput, SummarizeOutput);
+schema!(Translation, TranslationInput, TranslationOutput);
+schema!(Diffusion, DiffusionInput, DiffusionOutput);
+schema!(Llama, LlamaInput, LlamaOutput);
+
+#[cfg(test)]
+mod tests {
+    use super::{ModelInput, SchemaError, SentimentInput, TranslationInput};
+    use crate::{config::Limits, types::ModelType};
+    use serde_json::json;
+
+    const LIMITS: Limits = Limits {
+        diffusion_height: 1024,
+        diffusion_width: 768,
+        llama_sample_len: 256,
+    };
+
+    fn read(model_type: ModelType, value: serde_json::Value) -> Result<ModelInput, SchemaError> {
+        ModelInput::parse(model_type, value, &LIMITS)
+    }
+
+    #[test]
+    fn parse() {
+        let input = read(ModelType::Sentiment, json!({ "input": "great movie" }));
+        assert_eq!(
+            input,
+            Ok(ModelInput::Sentiment(SentimentInput {
+                input: "great movie".to_owned()
+            }))
+        );
+
+        let input = read(ModelType::Diffusion, json!({ "input": "great movie" }));
+        assert!(matches!(input, Err(SchemaError::Malformed { .. })));
+
+        let input = read(
+            ModelType::Translation,
+            json!({ "input": "hello", "source_lang": "en", "target_lang": "xx" }),
+        );
+        assert!(matches!(input, Err(SchemaError::Invalid { .. })));
+    }
+
+    #[test]
+    fn limits() {
+        let diffusion = |height: i64, width: i64| {
+            read(
+                ModelType::Diffusion,
+                json!({ "prompt": "robot", "seed": 42, "height": height, "width": width }),
+            )
+        };
+        assert!(diffusion(1024, 768).is_ok());
+        assert!(matches!(
+            diffusion(1032, 768),
+            Err(SchemaError::Invalid { .. })
+        ));
+        assert!(matches!(
+            diffusion(512, 776),
+            Err(SchemaError::Invalid { .. })
+        ));
+
+        let llama = |sample_len: usize| {
+            read(
+                ModelType::Llama,
+                json!({ "prompt": "once", "sample_len": sample_len, "temperature": 0.8 }),
+            )
+        };
+        assert!(llama(256).is_ok());
+        assert!(matches!(llama(0), Err(SchemaError::Invalid { .. })));
+        assert!(matches!(llama(257), Err(SchemaError::Invalid { .. })));
+    }
+
+    #[test]
+    fn mismatch() {
+        let input = ModelInput::from(SentimentInput {
+            input: "great movie".to_owned(),
+        });
+        assert_eq!(
+            TranslationInput::try_from(input),
+            Err(SchemaError::Mismatch {
+                expected: ModelType::Translation,
+                found: ModelType::Sentiment,
+            })
+        );
+    }
+}
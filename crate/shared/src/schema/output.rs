@@ -0,0 +1,28 @@
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct SentimentOutput {
+    pub label: String,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct SummarizeOutput {
+    pub summary: String,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct TranslationOutput {
+    pub translation: String,
+}
+
+/// `images` are paths relative to the server `/images` route.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct DiffusionOutput {
+    pub images: Vec<String>,
+    pub seed: i64,
+    pub steps: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct LlamaOutput {
+    pub text: String,
+}
@@ -50,3 +50,15 @@ pub fn wait_for_input() {
 pub fn root() -> String {
     crate::constants::role::ROOT.to_owned()
 }
+
+/// Resident memory of the current process in bytes, where `/proc` is available.
+pub fn resident_memory() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kilobytes: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kilobytes * 1024)
+}
+
+pub fn job_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
@@ -0,0 +1,26 @@
+use crate::constants::batch;
+
+/// How long a batching worker holds the first queued job while waiting for
+/// others to run with it: at most `max_wait` milliseconds, and never more
+/// than `max_size` jobs in one forward pass.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Batching {
+    pub max_size: usize,
+    pub max_wait: u64,
+}
+
+impl Default for Batching {
+    fn default() -> Self {
+        Self {
+            max_size: batch::MAX_SIZE,
+            max_wait: batch::MAX_WAIT,
+        }
+    }
+}
+
+impl Batching {
+    /// A batching worker always runs at least one job at a time.
+    pub fn max_size(&self) -> usize {
+        self.max_size.max(1)
+    }
+}
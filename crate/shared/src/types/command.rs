@@ -5,6 +5,7 @@ pub enum CommandType {
     Spawn,
     Resume,
     Process,
+    Cancel,
     // MoveTo,
     // ReConfig,
     // StreamTo
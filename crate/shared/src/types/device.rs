@@ -0,0 +1,83 @@
+/// Where a worker runs its model. `Auto` is only a hint, the supervisor
+/// resolves it to `Cpu` or a GPU before spawning the worker.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    serde::Deserialize,
+    serde::Serialize,
+)]
+#[serde(try_from = "String", into = "String")]
+pub enum Device {
+    Cpu,
+    Cuda(usize),
+    #[default]
+    Auto,
+}
+
+impl std::fmt::Display for Device {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Device::Cpu => write!(f, "cpu"),
+            Device::Cuda(index) => write!(f, "cuda:{index}"),
+            Device::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+impl std::str::FromStr for Device {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "cpu" => Ok(Device::Cpu),
+            "auto" => Ok(Device::Auto),
+            _ => value
+                .strip_prefix("cuda:")
+                .and_then(|index| index.parse().ok())
+                .map(Device::Cuda)
+                .ok_or_else(|| format!("unknown device `{value}`, expected cpu, cuda:N or auto")),
+        }
+    }
+}
+
+impl TryFrom<String> for Device {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Device> for String {
+    fn from(value: Device) -> Self {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Device;
+
+    #[test]
+    fn parse() {
+        assert_eq!("cpu".parse(), Ok(Device::Cpu));
+        assert_eq!("cuda:1".parse(), Ok(Device::Cuda(1)));
+        assert_eq!("auto".parse(), Ok(Device::Auto));
+        assert!("cuda".parse::<Device>().is_err());
+        assert!("gpu:0".parse::<Device>().is_err());
+    }
+
+    #[test]
+    fn serde() {
+        let device: Device = serde_json::from_str("\"cuda:0\"").unwrap();
+        assert_eq!(device, Device::Cuda(0));
+        assert_eq!(serde_json::to_string(&Device::Cpu).unwrap(), "\"cpu\"");
+    }
+}
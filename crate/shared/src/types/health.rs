@@ -0,0 +1,134 @@
+use super::ModelType;
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum WorkerState {
+    Loading,
+    Idle,
+    Busy,
+    Paused,
+}
+
+/// Job latencies of a worker over its recent jobs, in milliseconds.
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Latency {
+    pub average: u64,
+    pub p50: u64,
+    pub p95: u64,
+    pub p99: u64,
+}
+
+impl Latency {
+    pub fn from_samples(samples: &[u64]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+        let percentile = |rank: usize| sorted[(sorted.len() * rank / 100).min(sorted.len() - 1)];
+
+        Self {
+            average: sorted.iter().sum::<u64>() / sorted.len() as u64,
+            p50: percentile(50),
+            p95: percentile(95),
+            p99: percentile(99),
+        }
+    }
+}
+
+/// `memory` estimates what the worker costs, as the bytes the process grew
+/// by while it loaded its model; workers loading together share the growth.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct WorkerHealth {
+    pub task_id: String,
+    pub owner: String,
+    pub model_type: ModelType,
+    pub state: WorkerState,
+    pub processed: u64,
+    pub failures: u64,
+    pub latency: Latency,
+    pub queue_depth: usize,
+    pub device: Option<String>,
+    pub memory: Option<u64>,
+}
+
+/// Snapshot of the supervisor; `memory` is the resident size of the process
+/// in bytes, shared by every worker, and `jobs` the ids of the jobs it holds,
+/// queued or running.
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct HealthReport {
+    pub workers: Vec<WorkerHealth>,
+    pub memory: Option<u64>,
+    pub jobs: Vec<String>,
+}
+
+impl HealthReport {
+    /// The report as shown to `owner` when not an admin: without the job ids,
+    /// and without the owners of the workers of others.
+    pub fn redacted(&self, owner: &str) -> HealthReport {
+        let workers = self
+            .workers
+            .iter()
+            .cloned()
+            .map(|mut worker| {
+                if worker.owner != owner {
+                    worker.owner.clear();
+                }
+                worker
+            })
+            .collect();
+        HealthReport {
+            workers,
+            memory: self.memory,
+            jobs: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HealthReport, Latency, WorkerHealth, WorkerState};
+    use crate::types::ModelType;
+
+    #[test]
+    fn latency() {
+        assert_eq!(Latency::from_samples(&[]), Latency::default());
+
+        let samples: Vec<u64> = (1..=100).rev().collect();
+        let latency = Latency::from_samples(&samples);
+        assert_eq!(latency.average, 50);
+        assert_eq!(latency.p50, 51);
+        assert_eq!(latency.p95, 96);
+        assert_eq!(latency.p99, 100);
+    }
+
+    fn worker(owner: &str) -> WorkerHealth {
+        WorkerHealth {
+            task_id: format!("{owner} task"),
+            owner: owner.to_owned(),
+            model_type: ModelType::Sentiment,
+            state: WorkerState::Idle,
+            processed: 0,
+            failures: 0,
+            latency: Latency::default(),
+            queue_depth: 0,
+            device: None,
+            memory: None,
+        }
+    }
+
+    #[test]
+    fn redacted() {
+        let report = HealthReport {
+            workers: vec![worker("alice"), worker("bob")],
+            memory: Some(1024),
+            jobs: vec!["job".to_owned()],
+        };
+        let redacted = report.redacted("alice");
+        assert!(redacted.jobs.is_empty());
+        assert_eq!(redacted.memory, Some(1024));
+        assert_eq!(redacted.workers[0].owner, "alice");
+        assert_eq!(redacted.workers[1].owner, "");
+        assert_eq!(redacted.workers[1].task_id, "bob task");
+    }
+}
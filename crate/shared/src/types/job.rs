@@ -0,0 +1,53 @@
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn can_move_to(&self, next: JobStatus) -> bool {
+        use JobStatus::*;
+        matches!(
+            (self, next),
+            (Queued, Running) | (Queued, Cancelled) | (Running, Done | Failed | Cancelled)
+        )
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            JobStatus::Done | JobStatus::Failed | JobStatus::Cancelled
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::JobStatus::*;
+
+    #[test]
+    fn lifecycle() {
+        assert!(Queued.can_move_to(Running));
+        assert!(Queued.can_move_to(Cancelled));
+        assert!(Running.can_move_to(Done));
+        assert!(Running.can_move_to(Failed));
+        assert!(Running.can_move_to(Cancelled));
+
+        assert!(!Queued.can_move_to(Done));
+        assert!(!Running.can_move_to(Queued));
+        assert!(!Done.can_move_to(Running));
+        assert!(!Cancelled.can_move_to(Running));
+    }
+
+    #[test]
+    fn terminal() {
+        assert!(!Queued.is_terminal());
+        assert!(!Running.is_terminal());
+        assert!(Done.is_terminal());
+        assert!(Failed.is_terminal());
+        assert!(Cancelled.is_terminal());
+    }
+}
@@ -12,4 +12,27 @@ pub enum MessageType {
     ModelError,
     SchedulerStep,
     LlamaTokenGen,
+    JobQueued,
+    JobRunning,
+    JobDone,
+    JobFailed,
+    JobCancelled,
+    QueueFull,
+    QueuePosition,
+    ModelCrashed,
+    ModelEvicted,
+    ConfigReloaded,
+}
+
+impl MessageType {
+    pub fn job_status(&self) -> Option<super::JobStatus> {
+        match self {
+            MessageType::JobQueued => Some(super::JobStatus::Queued),
+            MessageType::JobRunning => Some(super::JobStatus::Running),
+            MessageType::JobDone => Some(super::JobStatus::Done),
+            MessageType::JobFailed => Some(super::JobStatus::Failed),
+            MessageType::JobCancelled => Some(super::JobStatus::Cancelled),
+            _ => None,
+        }
+    }
 }
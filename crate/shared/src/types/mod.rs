@@ -1,7 +1,25 @@
+pub mod batch;
 pub mod command;
+pub mod device;
+pub mod health;
+pub mod job;
 pub mod message;
 pub mod model;
+pub mod preload;
+pub mod residency;
+pub mod restart;
+pub mod role;
+pub mod scope;
 
+pub use batch::Batching;
 pub use command::CommandType;
+pub use device::Device;
+pub use health::{HealthReport, WorkerHealth, WorkerState};
+pub use job::JobStatus;
 pub use message::MessageType;
 pub use model::ModelType;
+pub use preload::Preload;
+pub use residency::Residency;
+pub use restart::RestartPolicy;
+pub use role::Role;
+pub use scope::Scope;
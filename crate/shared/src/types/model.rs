@@ -1,4 +1,6 @@
-#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+use crate::config::path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
 pub enum ModelType {
     Sentiment,
     Summarize,
@@ -6,3 +8,36 @@ pub enum ModelType {
     Diffusion,
     Llama,
 }
+
+impl ModelType {
+    /// Models too large to share a GPU with one another.
+    pub fn is_heavy(&self) -> bool {
+        matches!(self, ModelType::Diffusion | ModelType::Llama)
+    }
+
+    /// Folder of the model weights in `.ai-data`.
+    pub fn ressource(&self) -> &'static str {
+        match self {
+            ModelType::Sentiment => path::model::DISTILBERT_SST2,
+            ModelType::Summarize => path::model::DISTILBART_CNN_6_6,
+            ModelType::Translation => path::model::M2M100_418M,
+            ModelType::Diffusion => path::model::STABLE_DIFFUSION_2_1,
+            ModelType::Llama => path::model::LLAMA_7B,
+        }
+    }
+}
+
+impl std::str::FromStr for ModelType {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "sentiment" => Ok(ModelType::Sentiment),
+            "summarize" => Ok(ModelType::Summarize),
+            "translation" => Ok(ModelType::Translation),
+            "diffusion" => Ok(ModelType::Diffusion),
+            "llama" => Ok(ModelType::Llama),
+            _ => Err(format!("unknown model type `{value}`")),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use super::{Device, ModelType, RestartPolicy};
+
+fn one() -> usize {
+    1
+}
+
+/// A model spawned when the supervisor starts, `replicas` times on `device`.
+/// `restart` overrides the default restart policy of the model type.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Preload {
+    pub model_type: ModelType,
+    #[serde(default = "one")]
+    pub replicas: usize,
+    #[serde(default)]
+    pub device: Device,
+    #[serde(default)]
+    pub restart: Option<RestartPolicy>,
+}
+
+impl Preload {
+    pub fn new(model_type: ModelType) -> Self {
+        Self {
+            model_type,
+            replicas: 1,
+            device: Device::Auto,
+            restart: None,
+        }
+    }
+}
+
+/// Reads the `--model` flag of `airs server run`: a model type followed by
+/// an optional `*replicas` and `@device`, e.g. `diffusion*2@cuda:1`.
+impl std::str::FromStr for Preload {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (model, device) = match value.split_once('@') {
+            Some((model, device)) => (model, device.parse()?),
+            None => (value, Device::Auto),
+        };
+        let (model_type, replicas) = match model.split_once('*') {
+            Some((model_type, replicas)) => (
+                model_type,
+                replicas
+                    .parse()
+                    .map_err(|_| format!("invalid replica count `{replicas}`"))?,
+            ),
+            None => (model, 1),
+        };
+
+        Ok(Self {
+            model_type: model_type.parse()?,
+            replicas,
+            device,
+            restart: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Preload;
+    use crate::types::{Device, ModelType};
+
+    #[test]
+    fn parse() {
+        assert_eq!("sentiment".parse(), Ok(Preload::new(ModelType::Sentiment)));
+
+        let preload: Preload = "Diffusion*2@cuda:1".parse().unwrap();
+        assert_eq!(preload.model_type, ModelType::Diffusion);
+        assert_eq!(preload.replicas, 2);
+        assert_eq!(preload.device, Device::Cuda(1));
+
+        assert!("llama*two".parse::<Preload>().is_err());
+        assert!("bert".parse::<Preload>().is_err());
+        assert!("llama@gpu".parse::<Preload>().is_err());
+    }
+}
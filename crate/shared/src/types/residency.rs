@@ -0,0 +1,51 @@
+/// When the supervisor loads and unloads workers. In `lazy` mode a job for a
+/// model type without worker spawns one. Workers left idle for `idle_timeout`
+/// milliseconds are unloaded, and while the process holds more than
+/// `memory_budget` bytes the least recently used idle worker is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Residency {
+    pub lazy: bool,
+    pub idle_timeout: Option<u64>,
+    pub memory_budget: Option<u64>,
+}
+
+impl Residency {
+    pub fn is_expired(&self, idle: u64) -> bool {
+        matches!(self.idle_timeout, Some(timeout) if idle >= timeout)
+    }
+
+    pub fn is_over_budget(&self, memory: Option<u64>) -> bool {
+        match (self.memory_budget, memory) {
+            (Some(budget), Some(memory)) => memory > budget,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Residency;
+
+    #[test]
+    fn idle_timeout() {
+        let residency = Residency {
+            idle_timeout: Some(1_000),
+            ..Default::default()
+        };
+        assert!(!residency.is_expired(999));
+        assert!(residency.is_expired(1_000));
+        assert!(!Residency::default().is_expired(u64::MAX));
+    }
+
+    #[test]
+    fn memory_budget() {
+        let residency = Residency {
+            memory_budget: Some(1 << 30),
+            ..Default::default()
+        };
+        assert!(residency.is_over_budget(Some((1 << 30) + 1)));
+        assert!(!residency.is_over_budget(Some(1 << 30)));
+        assert!(!residency.is_over_budget(None));
+        assert!(!Residency::default().is_over_budget(Some(u64::MAX)));
+    }
+}
@@ -0,0 +1,64 @@
+use crate::constants::restart;
+
+/// How the supervisor brings a crashed worker back: each restart waits twice
+/// as long as the previous one, up to `max_backoff` milliseconds, and the
+/// worker is given up after `max_restarts` crashes.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct RestartPolicy {
+    pub max_restarts: u32,
+    pub backoff: u64,
+    pub max_backoff: u64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_restarts: restart::MAX_RESTARTS,
+            backoff: restart::BACKOFF,
+            max_backoff: restart::MAX_BACKOFF,
+        }
+    }
+}
+
+impl RestartPolicy {
+    pub fn allows(&self, restarts: u32) -> bool {
+        restarts < self.max_restarts
+    }
+
+    /// Milliseconds to wait before the restart following `restarts` previous ones.
+    pub fn delay(&self, restarts: u32) -> u64 {
+        2u64.checked_pow(restarts)
+            .and_then(|factor| self.backoff.checked_mul(factor))
+            .map_or(self.max_backoff, |delay| delay.min(self.max_backoff))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RestartPolicy;
+
+    #[test]
+    fn backoff() {
+        let policy = RestartPolicy {
+            max_restarts: 3,
+            backoff: 100,
+            max_backoff: 1_000,
+        };
+        assert_eq!(policy.delay(0), 100);
+        assert_eq!(policy.delay(1), 200);
+        assert_eq!(policy.delay(3), 800);
+        assert_eq!(policy.delay(4), 1_000);
+        assert_eq!(policy.delay(64), 1_000);
+    }
+
+    #[test]
+    fn max_restarts() {
+        let policy = RestartPolicy {
+            max_restarts: 2,
+            ..Default::default()
+        };
+        assert!(policy.allows(0));
+        assert!(policy.allows(1));
+        assert!(!policy.allows(2));
+    }
+}
@@ -0,0 +1,101 @@
+use super::{CommandType, ModelType};
+use crate::constants;
+
+/// What a user may do with the workers, read from the `role` column of the
+/// `users` table. Unknown values are read as `User`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    #[default]
+    User,
+    Admin,
+}
+
+impl std::str::FromStr for Role {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "user" => Ok(Role::User),
+            "admin" => Ok(Role::Admin),
+            _ => Err(format!("unknown role `{value}`")),
+        }
+    }
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Role::User => write!(f, "user"),
+            Role::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+impl Role {
+    /// Whether `user` may run the command on a worker, or a job, of `owner`,
+    /// running `model_type`. Admins may do anything. Users may spawn workers
+    /// of their own for the light models, control and cancel what they own,
+    /// and process on root workers too.
+    pub fn may(
+        &self,
+        command_type: CommandType,
+        model_type: ModelType,
+        user: &str,
+        owner: &str,
+    ) -> bool {
+        match self {
+            Role::Admin => true,
+            Role::User => match command_type {
+                CommandType::Spawn => owner == user && !model_type.is_heavy(),
+                CommandType::Process => owner == user || owner == constants::role::ROOT,
+                CommandType::Kill
+                | CommandType::Pause
+                | CommandType::Resume
+                | CommandType::Cancel => owner == user,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Role;
+    use crate::{
+        constants::role::ROOT,
+        types::{CommandType, ModelType::*},
+    };
+
+    #[test]
+    fn parse() {
+        assert_eq!("admin".parse(), Ok(Role::Admin));
+        assert_eq!("User".parse(), Ok(Role::User));
+        assert!("root".parse::<Role>().is_err());
+        assert_eq!(Role::Admin.to_string().parse(), Ok(Role::Admin));
+    }
+
+    #[test]
+    fn may() {
+        let user = Role::User;
+        let may = |command_type| user.may(command_type, Sentiment, "alice", ROOT);
+        assert!(may(CommandType::Process));
+        assert!(!may(CommandType::Kill));
+        assert!(user.may(CommandType::Process, Sentiment, "alice", "alice"));
+        assert!(!user.may(CommandType::Process, Sentiment, "alice", "bob"));
+        assert!(user.may(CommandType::Kill, Sentiment, "alice", "alice"));
+        assert!(!user.may(CommandType::Pause, Sentiment, "alice", "bob"));
+
+        assert!(Role::Admin.may(CommandType::Kill, Sentiment, "carol", ROOT));
+        assert!(Role::Admin.may(CommandType::Cancel, Sentiment, "carol", "bob"));
+    }
+
+    #[test]
+    fn spawn() {
+        let user = Role::User;
+        assert!(user.may(CommandType::Spawn, Sentiment, "alice", "alice"));
+        assert!(!user.may(CommandType::Spawn, Sentiment, "alice", "bob"));
+        assert!(!user.may(CommandType::Spawn, Diffusion, "alice", "alice"));
+        assert!(!user.may(CommandType::Spawn, Llama, "alice", "alice"));
+        assert!(Role::Admin.may(CommandType::Spawn, Llama, "carol", "carol"));
+    }
+}
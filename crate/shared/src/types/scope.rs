@@ -0,0 +1,60 @@
+use super::{CommandType, ModelType};
+
+/// What a request may run. Sessions may run anything, API keys only the
+/// model and command types they were created with.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Scope {
+    pub model_types: Option<Vec<ModelType>>,
+    pub command_types: Option<Vec<CommandType>>,
+}
+
+impl Scope {
+    pub fn full() -> Self {
+        Self {
+            model_types: None,
+            command_types: None,
+        }
+    }
+
+    pub fn restricted(model_types: Vec<ModelType>, command_types: Vec<CommandType>) -> Self {
+        Self {
+            model_types: Some(model_types),
+            command_types: Some(command_types),
+        }
+    }
+
+    pub fn is_restricted(&self) -> bool {
+        self.model_types.is_some() || self.command_types.is_some()
+    }
+
+    pub fn allows(&self, command_type: CommandType, model_type: ModelType) -> bool {
+        self.command_types
+            .iter()
+            .all(|command_types| command_types.contains(&command_type))
+            && self
+                .model_types
+                .iter()
+                .all(|model_types| model_types.contains(&model_type))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Scope;
+    use crate::types::{CommandType, ModelType};
+
+    #[test]
+    fn allows() {
+        assert!(Scope::full().allows(CommandType::Kill, ModelType::Llama));
+        assert!(!Scope::full().is_restricted());
+
+        let scope = Scope::restricted(vec![ModelType::Sentiment], vec![CommandType::Process]);
+        assert!(scope.is_restricted());
+        assert!(scope.allows(CommandType::Process, ModelType::Sentiment));
+        assert!(!scope.allows(CommandType::Process, ModelType::Llama));
+        assert!(!scope.allows(CommandType::Spawn, ModelType::Sentiment));
+        assert!(
+            !Scope::restricted(vec![], vec![]).allows(CommandType::Process, ModelType::Sentiment)
+        );
+    }
+}
@@ -0,0 +1,24 @@
+-- Add up migration script here
+
+CREATE TABLE
+    "jobs" (
+        id UUID NOT NULL PRIMARY KEY,
+        user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+        model_type VARCHAR(50) NOT NULL,
+        input JSONB NOT NULL,
+        status VARCHAR(50) NOT NULL DEFAULT 'Queued',
+        output JSONB,
+        error VARCHAR,
+        created_at TIMESTAMP
+        WITH
+            TIME ZONE NOT NULL DEFAULT NOW(),
+        started_at TIMESTAMP
+        WITH
+            TIME ZONE,
+        finished_at TIMESTAMP
+        WITH
+            TIME ZONE,
+        duration_ms BIGINT
+    );
+
+CREATE INDEX jobs_user_id_created_at_idx ON jobs (user_id, created_at DESC);
@@ -0,0 +1,3 @@
+-- Add down migration script here
+
+ALTER TABLE "users" DROP COLUMN IF EXISTS tokens_revoked_at, DROP COLUMN IF EXISTS disabled;
@@ -0,0 +1,7 @@
+-- Add up migration script here
+
+ALTER TABLE "users"
+    ADD COLUMN disabled BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN tokens_revoked_at TIMESTAMP
+    WITH
+        TIME ZONE;
@@ -0,0 +1,29 @@
+-- Add up migration script here
+
+CREATE TABLE
+    "refresh_tokens" (
+        id UUID NOT NULL PRIMARY KEY DEFAULT (uuid_generate_v4()),
+        user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+        token_hash VARCHAR(64) NOT NULL UNIQUE,
+        expires_at TIMESTAMP
+        WITH
+            TIME ZONE NOT NULL,
+        created_at TIMESTAMP
+        WITH
+            TIME ZONE NOT NULL DEFAULT NOW(),
+        revoked_at TIMESTAMP
+        WITH
+            TIME ZONE,
+        replaced_by UUID
+    );
+
+CREATE INDEX refresh_tokens_user_id_idx ON refresh_tokens (user_id);
+
+CREATE TABLE
+    "revoked_tokens" (
+        jti UUID NOT NULL PRIMARY KEY,
+        user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+        expires_at TIMESTAMP
+        WITH
+            TIME ZONE NOT NULL
+    );
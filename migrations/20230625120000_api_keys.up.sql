@@ -0,0 +1,26 @@
+-- Add up migration script here
+
+CREATE TABLE
+    "api_keys" (
+        id UUID NOT NULL PRIMARY KEY DEFAULT (uuid_generate_v4()),
+        user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+        name VARCHAR(255) NOT NULL,
+        prefix VARCHAR(16) NOT NULL,
+        key_hash VARCHAR(64) NOT NULL UNIQUE,
+        model_types JSONB NOT NULL,
+        command_types JSONB NOT NULL,
+        expires_at TIMESTAMP
+        WITH
+            TIME ZONE,
+        created_at TIMESTAMP
+        WITH
+            TIME ZONE NOT NULL DEFAULT NOW(),
+        last_used_at TIMESTAMP
+        WITH
+            TIME ZONE,
+        revoked_at TIMESTAMP
+        WITH
+            TIME ZONE
+    );
+
+CREATE INDEX api_keys_user_id_idx ON api_keys (user_id);
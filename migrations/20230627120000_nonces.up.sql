@@ -0,0 +1,17 @@
+-- Add up migration script here
+
+CREATE TABLE
+    "login_nonces" (
+        nonce VARCHAR(64) NOT NULL PRIMARY KEY,
+        created_at TIMESTAMP
+        WITH
+            TIME ZONE NOT NULL DEFAULT NOW(),
+        expires_at TIMESTAMP
+        WITH
+            TIME ZONE NOT NULL,
+        used_at TIMESTAMP
+        WITH
+            TIME ZONE
+    );
+
+CREATE INDEX login_nonces_expires_at_idx ON login_nonces (expires_at);
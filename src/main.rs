@@ -4,6 +4,9 @@ use models::llama::Llama;
 use models::sentiment::Sentiment;
 use models::summarize::Summarize;
 use models::translation::Translation;
+use shared::config::Flags;
+use shared::types::Preload;
+use std::net::Ipv4Addr;
 
 #[derive(Debug, Clone, Subcommand)]
 pub enum LoaderCmd {
@@ -61,11 +64,26 @@ pub enum DiffusionCmd {
 #[derive(Debug, Clone, Subcommand)]
 pub enum ServerCmd {
     Run {
-        port: Option<String>,
-        ip: Option<String>,
+        #[arg(long)]
+        port: Option<u16>,
+        #[arg(long)]
+        address: Option<Ipv4Addr>,
+        /// Allowed CORS origin replacing the configured ones; can be repeated.
+        #[arg(long = "origin")]
+        origins: Vec<String>,
+        /// Model to preload instead of the configured ones, as
+        /// `<model>[*replicas][@device]`; can be repeated.
+        #[arg(long = "model")]
+        models: Vec<Preload>,
     },
 }
 
+#[derive(Debug, Clone, Subcommand)]
+pub enum AdminCmd {
+    /// Makes the user of a base58 pubkey an admin, against `DATABASE_URL`.
+    Bootstrap { pubkey: String },
+}
+
 #[derive(Debug, Clone, Subcommand)]
 pub enum LlmCmd {
     Translate {
@@ -106,6 +124,10 @@ pub enum Command {
         #[command(subcommand)]
         cmd: LlmCmd,
     },
+    Admin {
+        #[command(subcommand)]
+        cmd: AdminCmd,
+    },
 }
 
 #[derive(Parser, Clone, Debug)]
@@ -170,7 +192,20 @@ async fn main() -> Result<(), &'static str> {
             }),
         },
         Command::Server { cmd } => match cmd {
-            ServerCmd::Run { port, ip } => server::run(ip, port).await,
+            ServerCmd::Run {
+                port,
+                address,
+                origins,
+                models,
+            } => {
+                server::run(Flags {
+                    port,
+                    address: address.map(|address| address.octets()),
+                    cors_allowed_origins: origins,
+                    preload: models,
+                })
+                .await
+            }
         },
         Command::Llm { cmd } => match cmd {
             LlmCmd::Translate {
@@ -186,5 +221,8 @@ async fn main() -> Result<(), &'static str> {
                 temperature,
             } => Llama::default().try_prediction(&prompt, sample_len, temperature, None),
         },
+        Command::Admin { cmd } => match cmd {
+            AdminCmd::Bootstrap { pubkey } => server::db::bootstrap_admin(&pubkey).await,
+        },
     }
 }